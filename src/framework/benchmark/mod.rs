@@ -0,0 +1,22 @@
+use wgpu::Features;
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        adapter_selection::AdapterSelection, api_trace::ApiTrace,
+        device_requirements::DeviceRequirements, gpu_context::GPUContext,
+    },
+};
+
+pub mod report;
+pub mod runner;
+pub mod statistics;
+
+// Context benchmarks run on, with timestamp queries enabled when the adapter supports them
+pub fn benchmark_context() -> Result<GPUContext, FrameworkError> {
+    GPUContext::new_blocking(
+        &AdapterSelection::from_env(),
+        &DeviceRequirements::new().with_optional_features(Features::TIMESTAMP_QUERY),
+        &ApiTrace::new(),
+    )
+}
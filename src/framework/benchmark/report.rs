@@ -0,0 +1,163 @@
+use std::fmt::{self, Write};
+
+use serde::Serialize;
+
+use super::statistics::Statistics;
+
+// Columns of BenchmarkReport::to_csv(), times in milliseconds
+pub const CSV_HEADER: &str = "name,adapter,warmup_runs,runs,\
+wall_mean_ms,wall_median_ms,wall_std_dev_ms,wall_min_ms,wall_max_ms,\
+gpu_mean_ms,gpu_median_ms,gpu_std_dev_ms,gpu_min_ms,gpu_max_ms,\
+bytes_per_run,operations_per_run,bandwidth_gb_per_s,operations_per_s";
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BenchmarkResult {
+    pub name: String,
+    pub adapter: String,
+    pub warmup_runs: u32,
+    pub runs: usize,
+    // Milliseconds per run
+    pub wall_time: Statistics,
+    // Only measured with the TIMESTAMP_QUERY feature
+    pub gpu_time: Option<Statistics>,
+    pub bytes_per_run: Option<u64>,
+    pub operations_per_run: Option<u64>,
+    // Rates over the median run time, from GPU times when measured
+    pub bandwidth_gb_per_s: Option<f64>,
+    pub operations_per_s: Option<f64>,
+}
+
+impl BenchmarkResult {
+    pub fn new(
+        name: &str,
+        adapter: &str,
+        warmup_runs: u32,
+        wall_times: &[f64],
+        gpu_times: Option<&[f64]>,
+        bytes_per_run: Option<u64>,
+        operations_per_run: Option<u64>,
+    ) -> Self {
+        let wall_time: Statistics = Statistics::from_samples(wall_times);
+        let gpu_time: Option<Statistics> = gpu_times.map(Statistics::from_samples);
+        let seconds: f64 = gpu_time.unwrap_or(wall_time).median / 1e3;
+        let rate = |amount: Option<u64>| {
+            amount
+                .filter(|_| seconds > 0.0)
+                .map(|amount| amount as f64 / seconds)
+        };
+        Self {
+            name: name.to_owned(),
+            adapter: adapter.to_owned(),
+            warmup_runs,
+            runs: wall_time.count,
+            wall_time,
+            gpu_time,
+            bytes_per_run,
+            operations_per_run,
+            bandwidth_gb_per_s: rate(bytes_per_run).map(|bytes_per_s| bytes_per_s / 1e9),
+            operations_per_s: rate(operations_per_run),
+        }
+    }
+
+    fn write_csv_row(&self, csv: &mut String) {
+        let optional = |value: Option<f64>| value.map_or(String::new(), |value| value.to_string());
+        let statistics = |statistics: Option<Statistics>| {
+            [
+                statistics.map(|s| s.mean),
+                statistics.map(|s| s.median),
+                statistics.map(|s| s.std_dev),
+                statistics.map(|s| s.min),
+                statistics.map(|s| s.max),
+            ]
+            .map(optional)
+            .join(",")
+        };
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&self.name),
+            csv_field(&self.adapter),
+            self.warmup_runs,
+            self.runs,
+            statistics(Some(self.wall_time)),
+            statistics(self.gpu_time),
+            optional(self.bytes_per_run.map(|bytes| bytes as f64)),
+            optional(self.operations_per_run.map(|operations| operations as f64)),
+            optional(self.bandwidth_gb_per_s),
+            optional(self.operations_per_s),
+        );
+    }
+}
+
+// One line: median and spread of the times, then the rates
+impl fmt::Display for BenchmarkResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Statistics {
+            mean,
+            median,
+            std_dev,
+            ..
+        } = self.wall_time;
+        write!(
+            f,
+            "{}: wall {median:.3} ms median ({mean:.3} ± {std_dev:.3} ms)",
+            self.name
+        )?;
+        if let Some(Statistics {
+            mean,
+            median,
+            std_dev,
+            ..
+        }) = self.gpu_time
+        {
+            write!(
+                f,
+                ", GPU {median:.3} ms median ({mean:.3} ± {std_dev:.3} ms)"
+            )?;
+        }
+        if let Some(bandwidth) = self.bandwidth_gb_per_s {
+            write!(f, ", {bandwidth:.2} GB/s")?;
+        }
+        if let Some(operations) = self.operations_per_s {
+            write!(f, ", {operations:.3e} op/s")?;
+        }
+        Ok(())
+    }
+}
+
+// Results of several benchmarks, e.g. variants of a kernel, to export for tracking
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BenchmarkReport {
+    pub results: Vec<BenchmarkResult>,
+}
+
+impl BenchmarkReport {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push(&mut self, result: BenchmarkResult) {
+        self.results.push(result);
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    // Header and one row per result, unmeasured values are left empty
+    pub fn to_csv(&self) -> String {
+        let mut csv: String = format!("{CSV_HEADER}\n");
+        for result in &self.results {
+            result.write_csv_row(&mut csv);
+        }
+        csv
+    }
+}
+
+// Quoted when it contains a separator, a quote or a line break
+fn csv_field(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => field.to_owned(),
+    }
+}
@@ -0,0 +1,205 @@
+use wgpu::{
+    BindGroup, CommandEncoder, CommandEncoderDescriptor, ComputePassDescriptor, Maintain,
+    SubmissionIndex,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{compute_kernel::ComputeKernel, gpu_context::GPUContext, gpu_timer::GpuTimer},
+};
+
+use super::report::BenchmarkResult;
+
+// Warms up, then times a number of runs of recorded GPU work, each submitted on its own and
+// waited for before the next one
+// -> Wall times span recording, submission and completion, so they include CPU overhead such
+//    as creating parameter buffers. GPU times only span the recorded commands, and are
+//    measured when the device has the TIMESTAMP_QUERY feature (see benchmark_context())
+// -> Bytes and operations per run give the effective bandwidth and operation rate, e.g.
+//    8 * n bytes for a kernel reading and writing n f32
+pub struct Benchmark {
+    name: String,
+    warmup_runs: u32,
+    runs: u32,
+    bytes_per_run: Option<u64>,
+    operations_per_run: Option<u64>,
+}
+
+impl Benchmark {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            warmup_runs: 3,
+            runs: 20,
+            bytes_per_run: None,
+            operations_per_run: None,
+        }
+    }
+
+    // <---- Builder ---->
+    pub fn with_warmup_runs(mut self, warmup_runs: u32) -> Self {
+        self.warmup_runs = warmup_runs;
+        self
+    }
+
+    // At least one
+    pub fn with_runs(mut self, runs: u32) -> Self {
+        self.runs = runs.max(1);
+        self
+    }
+
+    // Bytes read and written by each run
+    pub fn with_bytes(mut self, bytes_per_run: u64) -> Self {
+        self.bytes_per_run = Some(bytes_per_run);
+        self
+    }
+
+    // Operations (e.g. elements processed or flops) performed by each run
+    pub fn with_operations(mut self, operations_per_run: u64) -> Self {
+        self.operations_per_run = Some(operations_per_run);
+        self
+    }
+
+    // <---- Running ---->
+    // `record` is called once per run, e.g. with the record() function of a primitive
+    #[track_caller]
+    pub fn run(
+        &self,
+        context: &GPUContext,
+        mut record: impl FnMut(&mut CommandEncoder) -> Result<(), FrameworkError>,
+    ) -> Result<BenchmarkResult, FrameworkError> {
+        let (device, queue) = (&context.device, &context.queue);
+        // Runs past the timer's capacity reuse its intervals, once the earlier ones are read
+        let timer: Option<GpuTimer> = match GpuTimer::is_supported(device) {
+            true => Some(GpuTimer::new(
+                device,
+                self.runs.min(GpuTimer::MAX_CAPACITY),
+                Some(&self.name),
+            )?),
+            false => None,
+        };
+
+        let mut wall_times: Vec<f64> = Vec::with_capacity(self.runs as usize);
+        let mut gpu_times: Vec<f64> = Vec::with_capacity(self.runs as usize);
+        for run in 0..self.warmup_runs + self.runs {
+            // Index among measured runs
+            let measured: Option<u32> = run.checked_sub(self.warmup_runs);
+            let interval: Option<u32> = timer
+                .as_ref()
+                .zip(measured)
+                .map(|(timer, index)| index % timer.capacity());
+            let start: web_time::Instant = web_time::Instant::now();
+            let mut encoder: CommandEncoder =
+                device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("benchmark_encoder"),
+                });
+            if let (Some(timer), Some(interval)) = (&timer, interval) {
+                timer.record_begin(&mut encoder, interval);
+            }
+            record(&mut encoder)?;
+            if let (Some(timer), Some(interval)) = (&timer, interval) {
+                timer.record_end(&mut encoder, interval);
+            }
+            let submission: SubmissionIndex = queue.submit(Some(encoder.finish()));
+            device.poll(Maintain::WaitForSubmissionIndex(submission));
+            if measured.is_some() {
+                wall_times.push(start.elapsed().as_secs_f64() * 1e3);
+            }
+
+            // Read once every interval is used or the last run is done, outside the wall time
+            if let (Some(timer), Some(interval)) = (&timer, interval) {
+                if interval + 1 == timer.capacity() || run + 1 == self.warmup_runs + self.runs {
+                    gpu_times.extend(
+                        timer
+                            .read(device, queue, interval + 1)?
+                            .into_iter()
+                            .map(|nanoseconds| nanoseconds / 1e6),
+                    );
+                }
+            }
+        }
+
+        let gpu_times: Option<&[f64]> = timer.is_some().then_some(gpu_times.as_slice());
+        let result: BenchmarkResult = BenchmarkResult::new(
+            &self.name,
+            &context.adapter.get_info().name,
+            self.warmup_runs,
+            &wall_times,
+            gpu_times,
+            self.bytes_per_run,
+            self.operations_per_run,
+        );
+        log::info!("{result}");
+        Ok(result)
+    }
+
+    // Panics if the kernel was built without buffers
+    #[track_caller]
+    pub fn run_kernel(
+        &self,
+        context: &GPUContext,
+        kernel: &ComputeKernel,
+        workgroups: (u32, u32, u32),
+    ) -> Result<BenchmarkResult, FrameworkError> {
+        let bind_group: &BindGroup = kernel
+            .bind_group()
+            .expect("Kernel built without buffers must be benchmarked with a bind group");
+        self.run_kernel_with(context, kernel, bind_group, workgroups)
+    }
+
+    #[track_caller]
+    pub fn run_kernel_with(
+        &self,
+        context: &GPUContext,
+        kernel: &ComputeKernel,
+        bind_group: &BindGroup,
+        workgroups: (u32, u32, u32),
+    ) -> Result<BenchmarkResult, FrameworkError> {
+        self.run(context, |encoder| {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: kernel.label(),
+                timestamp_writes: None,
+            });
+            kernel.dispatch_with(&mut compute_pass, bind_group, workgroups);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::Features;
+
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{
+            device_requirements::DeviceRequirements, gpu_context::GPUContext, gpu_timer::GpuTimer,
+        },
+        verification::verification_context_with_requirements,
+    };
+
+    use super::{Benchmark, BenchmarkResult};
+
+    #[test]
+    fn runs_past_timer_capacity() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context_with_requirements(
+            &DeviceRequirements::new().with_optional_features(Features::TIMESTAMP_QUERY),
+        )?;
+        let runs: u32 = GpuTimer::MAX_CAPACITY + 5;
+        let mut recorded: u32 = 0;
+        let result: BenchmarkResult = Benchmark::new("empty")
+            .with_warmup_runs(1)
+            .with_runs(runs)
+            .run(&context, |_| {
+                recorded += 1;
+                Ok(())
+            })?;
+        assert_eq!(recorded, runs + 1);
+        assert_eq!(result.runs, runs as usize);
+        assert_eq!(
+            result.gpu_time.map(|gpu_time| gpu_time.count),
+            GpuTimer::is_supported(&context.device).then_some(runs as usize)
+        );
+        Ok(())
+    }
+}
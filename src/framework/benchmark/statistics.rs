@@ -0,0 +1,48 @@
+use serde::Serialize;
+
+// Summary of a set of timing samples, in milliseconds
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Statistics {
+    pub count: usize,
+    pub mean: f64,
+    pub median: f64,
+    // Sample standard deviation, 0 for fewer than two samples
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Statistics {
+    // All zeros for no samples
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let count: usize = samples.len();
+        let mut sorted: Vec<f64> = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let median: f64 = match count % 2 {
+            1 => sorted[count / 2],
+            _ => (sorted[count / 2 - 1] + sorted[count / 2]) / 2.0,
+        };
+        let mean: f64 = samples.iter().sum::<f64>() / count as f64;
+        let variance: f64 = match count {
+            1 => 0.0,
+            _ => {
+                samples
+                    .iter()
+                    .map(|sample| (sample - mean).powi(2))
+                    .sum::<f64>()
+                    / (count - 1) as f64
+            }
+        };
+        Self {
+            count,
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            min: sorted[0],
+            max: sorted[count - 1],
+        }
+    }
+}
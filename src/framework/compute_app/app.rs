@@ -0,0 +1,332 @@
+use std::mem::size_of;
+
+use bytemuck::Pod;
+use rustc_hash::FxHashMap;
+use wgpu::{
+    Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor, ComputePass,
+    ComputePassDescriptor,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        adapter_selection::AdapterSelection,
+        api_trace::ApiTrace,
+        compute_kernel::ComputeKernel,
+        device_requirements::DeviceRequirements,
+        gpu_buffer::GpuBuffer,
+        gpu_context::GPUContext,
+        indirect::{DispatchIndirectArgs, IndirectArgs},
+    },
+};
+
+pub type ComputeTaskMap = FxHashMap<&'static str, ComputeTask>;
+// Buffers by label, at their binding, as 32-bit words so they can hold any 4-byte aligned type
+pub type ComputeBufferMap = FxHashMap<&'static str, (u32, GpuBuffer<u32>)>;
+
+// Compute kernel with the labels of the buffers bound to it
+pub struct ComputeTask {
+    kernel: ComputeKernel,
+    buffer_labels: Vec<&'static str>,
+}
+
+// Dispatch of a registered kernel over a number of workgroups
+#[derive(Clone, Copy, Debug)]
+pub struct Dispatch {
+    pub kernel: &'static str,
+    pub workgroups: Workgroups,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Workgroups {
+    Count(u32, u32, u32),
+    // Read from the DispatchIndirectArgs at `index` of a buffer added with
+    // add_indirect_buffer(), e.g. written by an earlier dispatch of the same run
+    Indirect { buffer: &'static str, index: u32 },
+}
+
+impl Dispatch {
+    pub fn new(kernel: &'static str, workgroups: (u32, u32, u32)) -> Self {
+        let (x, y, z) = workgroups;
+        Self {
+            kernel,
+            workgroups: Workgroups::Count(x, y, z),
+        }
+    }
+
+    pub fn indirect(kernel: &'static str, buffer: &'static str, index: u32) -> Self {
+        Self {
+            kernel,
+            workgroups: Workgroups::Indirect { buffer, index },
+        }
+    }
+}
+
+pub struct ComputeApp {
+    pub gpu_context: GPUContext,
+    pub buffers: ComputeBufferMap,
+    pub tasks: ComputeTaskMap,
+}
+
+impl ComputeApp {
+    pub fn new() -> Result<Self, FrameworkError> {
+        Self::new_with_settings(
+            &AdapterSelection::from_env(),
+            &DeviceRequirements::new(),
+            &ApiTrace::from_env(),
+        )
+    }
+
+    pub fn new_with_settings(
+        selection: &AdapterSelection,
+        requirements: &DeviceRequirements,
+        trace: &ApiTrace,
+    ) -> Result<Self, FrameworkError> {
+        log::debug!("Initialising GPU...");
+        let gpu_context: GPUContext = GPUContext::new_blocking(selection, requirements, trace)?;
+        log::debug!("Initialised GPU successfuly");
+        Ok(Self::new_with_context(gpu_context))
+    }
+
+    pub fn new_with_context(gpu_context: GPUContext) -> Self {
+        Self {
+            gpu_context,
+            buffers: Default::default(),
+            tasks: Default::default(),
+        }
+    }
+
+    // <---- Buffers ---->
+    // Buffers added again with the same label are replaced, kernels are rebound on their next run
+    pub fn add_storage_buffer<T: Pod>(
+        &mut self,
+        label: &'static str,
+        binding: u32,
+        contents: &[T],
+    ) -> Result<(), FrameworkError> {
+        self.add_buffer_with_usage(label, binding, contents, BufferUsages::STORAGE)
+    }
+
+    pub fn add_uniform_buffer<T: Pod>(
+        &mut self,
+        label: &'static str,
+        binding: u32,
+        contents: &T,
+    ) -> Result<(), FrameworkError> {
+        self.add_buffer_with_usage(
+            label,
+            binding,
+            std::slice::from_ref(contents),
+            BufferUsages::UNIFORM,
+        )
+    }
+
+    // Indirect arguments, also bound as a storage buffer so that kernels can write them
+    pub fn add_indirect_buffer<A: IndirectArgs>(
+        &mut self,
+        label: &'static str,
+        binding: u32,
+        args: &[A],
+    ) -> Result<(), FrameworkError> {
+        self.add_buffer_with_usage(
+            label,
+            binding,
+            args,
+            BufferUsages::INDIRECT | BufferUsages::STORAGE,
+        )
+    }
+
+    fn add_buffer_with_usage<T: Pod>(
+        &mut self,
+        label: &'static str,
+        binding: u32,
+        contents: &[T],
+        usage: BufferUsages,
+    ) -> Result<(), FrameworkError> {
+        let buffer: GpuBuffer<u32> = GpuBuffer::new(
+            &self.gpu_context.device,
+            &bytemuck::pod_collect_to_vec(contents),
+            usage,
+            Some(label),
+        )?;
+        self.buffers.insert(label, (binding, buffer));
+        Ok(())
+    }
+
+    // Overwrites the start of the buffer, which must be large enough
+    pub fn write_buffer<T: Pod>(&self, label: &str, contents: &[T]) -> Result<(), FrameworkError> {
+        let (_binding, buffer) = self.buffer_entry(label)?;
+        buffer.write_range(
+            &self.gpu_context.queue,
+            0,
+            &bytemuck::pod_collect_to_vec(contents),
+        )
+    }
+
+    // Blocks until the buffer contents are available
+    pub fn read_buffer<T: Pod>(&self, label: &str) -> Result<Vec<T>, FrameworkError> {
+        let (_binding, buffer) = self.buffer_entry(label)?;
+        let contents: Vec<u32> = buffer.read(&self.gpu_context.device, &self.gpu_context.queue)?;
+        Ok(bytemuck::pod_collect_to_vec(&contents))
+    }
+
+    fn buffer_entry(&self, label: &str) -> Result<&(u32, GpuBuffer<u32>), FrameworkError> {
+        self.buffers
+            .get(label)
+            .ok_or_else(|| FrameworkError::UnknownBuffer(label.to_owned()))
+    }
+
+    // <---- Kernels ---->
+    // Buffers are bound at the binding they were added with, in bind group 0
+    // -> Every binding of bind group 0 used by the entry point must be listed
+    pub fn add_kernel(
+        &mut self,
+        label: &'static str,
+        source: &str,
+        entry_point: &str,
+        buffer_labels: &[&'static str],
+    ) -> Result<(), FrameworkError> {
+        // Check buffers exist
+        for buffer_label in buffer_labels {
+            self.buffer_entry(buffer_label)?;
+        }
+        // Bound on the first run
+        let kernel: ComputeKernel = ComputeKernel::builder(source, entry_point)
+            .with_label(label)
+            .build(&self.gpu_context.device)?;
+        self.tasks.insert(
+            label,
+            ComputeTask {
+                kernel,
+                buffer_labels: buffer_labels.to_vec(),
+            },
+        );
+        Ok(())
+    }
+
+    fn task(&self, label: &str) -> Result<&ComputeTask, FrameworkError> {
+        self.tasks
+            .get(label)
+            .ok_or_else(|| FrameworkError::UnknownKernel(label.to_owned()))
+    }
+
+    // Binds the current buffers of a task, its bind group is only recreated when one of them
+    // was replaced
+    fn bind_task(&mut self, label: &str) -> Result<(), FrameworkError> {
+        let task: &mut ComputeTask = self
+            .tasks
+            .get_mut(label)
+            .ok_or_else(|| FrameworkError::UnknownKernel(label.to_owned()))?;
+        let bindings: Vec<(u32, &Buffer)> = task
+            .buffer_labels
+            .iter()
+            .map(|buffer_label| {
+                let (binding, buffer) = self
+                    .buffers
+                    .get(buffer_label)
+                    .ok_or_else(|| FrameworkError::UnknownBuffer((*buffer_label).to_owned()))?;
+                Ok((*binding, buffer.buffer()))
+            })
+            .collect::<Result<Vec<(u32, &Buffer)>, FrameworkError>>()?;
+        task.kernel.bind(&self.gpu_context.device, &bindings)?;
+        Ok(())
+    }
+
+    // <---- Execution ---->
+    // Records every dispatch in order into a single compute pass and submits it
+    pub fn run(&mut self, dispatches: &[Dispatch]) -> Result<(), FrameworkError> {
+        for dispatch in dispatches {
+            self.bind_task(dispatch.kernel)?;
+        }
+
+        // Create command encoder
+        let mut encoder: CommandEncoder =
+            self.gpu_context
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("compute_encoder"),
+                });
+
+        // Dispatch
+        {
+            let mut compute_pass: ComputePass =
+                encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: Some("compute_pass"),
+                    timestamp_writes: None,
+                });
+            for dispatch in dispatches {
+                let kernel: &ComputeKernel = &self.task(dispatch.kernel)?.kernel;
+                match dispatch.workgroups {
+                    Workgroups::Count(x, y, z) => kernel.dispatch(&mut compute_pass, (x, y, z)),
+                    Workgroups::Indirect { buffer, index } => {
+                        let (_binding, buffer) = self.buffer_entry(buffer)?;
+                        let offset: u64 =
+                            (index as usize * size_of::<DispatchIndirectArgs>()) as u64;
+                        kernel.dispatch_indirect_with(
+                            &mut compute_pass,
+                            kernel
+                                .bind_group()
+                                .expect("Kernels are bound before dispatch"),
+                            buffer.buffer(),
+                            offset,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Submit commands
+        self.gpu_context.queue.submit(Some(encoder.finish()));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{error::FrameworkError, verification::verification_context};
+
+    use super::{ComputeApp, Dispatch};
+
+    const SQUARE_WGSL: &str = include_str!("shaders/example.wgsl");
+
+    fn square_app(input: &[f32]) -> Result<ComputeApp, FrameworkError> {
+        let mut app: ComputeApp = ComputeApp::new_with_context(verification_context()?);
+        app.add_storage_buffer("input", 0, input)?;
+        app.add_storage_buffer("output", 1, &vec![0.0f32; input.len()])?;
+        app.add_kernel("square", SQUARE_WGSL, "main", &["input", "output"])?;
+        Ok(app)
+    }
+
+    #[test]
+    fn writes_past_the_buffer_are_rejected() -> Result<(), FrameworkError> {
+        let mut app: ComputeApp = square_app(&[1.0, 2.0, 3.0])?;
+        app.write_buffer("input", &[4.0f32, 5.0])?;
+        assert!(matches!(
+            app.write_buffer("input", &[0.0f32; 4]),
+            Err(FrameworkError::InvalidBufferRange { .. })
+        ));
+        assert!(matches!(
+            app.write_buffer("missing", &[0.0f32]),
+            Err(FrameworkError::UnknownBuffer(_))
+        ));
+
+        app.run(&[Dispatch::new("square", (1, 1, 1))])?;
+        assert_eq!(app.read_buffer::<f32>("output")?, [16.0, 25.0, 9.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn replaced_buffers_are_rebound() -> Result<(), FrameworkError> {
+        let mut app: ComputeApp = square_app(&[1.0, 2.0])?;
+        app.run(&[Dispatch::new("square", (1, 1, 1))])?;
+        assert_eq!(app.read_buffer::<f32>("output")?, [1.0, 4.0]);
+
+        let input: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        app.add_storage_buffer("input", 0, &input)?;
+        app.add_storage_buffer("output", 1, &vec![0.0f32; input.len()])?;
+        app.run(&[Dispatch::new("square", (2, 1, 1))])?;
+        let squares: Vec<f32> = input.iter().map(|x| x * x).collect();
+        assert_eq!(app.read_buffer::<f32>("output")?, squares);
+        Ok(())
+    }
+}
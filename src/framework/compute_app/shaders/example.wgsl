@@ -0,0 +1,14 @@
+@group(0) @binding(0)
+var<storage, read> input: array<f32>;
+
+@group(0) @binding(1)
+var<storage, read_write> output: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index: u32 = id.x;
+    if index >= arrayLength(&output) {
+        return;
+    }
+    output[index] = input[index] * input[index];
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+use wgpu::{
+    BufferAsyncError, CreateSurfaceError, Features, RequestDeviceError, SurfaceError, TextureFormat,
+};
+use winit::error::{EventLoopError, OsError};
+
+#[derive(Debug)]
+pub enum FrameworkError {
+    // Windowing
+    EventLoop(EventLoopError),
+    WindowCreation(OsError),
+    // GPU initialisation
+    SurfaceCreation(CreateSurfaceError),
+    AdapterNotFound,
+    MissingFeatures(Features),
+    // (limit name, requested, allowed)
+    UnsupportedLimits(Vec<(&'static str, u64, u64)>),
+    DeviceRequest(RequestDeviceError),
+    UnsupportedSurface,
+    // Resource creation (strict validation only)
+    Validation {
+        label: String,
+        location: String,
+        message: String,
+    },
+    // Rendering
+    SurfaceTexture(SurfaceError),
+    // Compute
+    UnknownBuffer(String),
+    UnknownKernel(String),
+    ShaderReflection {
+        label: String,
+        message: String,
+    },
+    GraphCycle(Vec<String>),
+    DimensionMismatch(String),
+    // Same buffer given for operands that must be distinct
+    AliasedBuffers(String),
+    // Transform length with a prime factor larger than the largest supported radix
+    UnsupportedFftLength(u32),
+    // GPU results outside the tolerance of the CPU reference, with the comparison summary
+    VerificationFailed(String),
+    BufferRead(BufferAsyncError),
+    InvalidBufferRange {
+        label: String,
+        message: String,
+    },
+    UnsupportedTextureFormat(TextureFormat),
+    // Image files
+    Image(image::ImageError),
+}
+
+impl fmt::Display for FrameworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EventLoop(err) => write!(f, "Event loop error: {err}"),
+            Self::WindowCreation(err) => write!(f, "Failed to create window: {err}"),
+            Self::SurfaceCreation(err) => write!(f, "Failed to create surface: {err}"),
+            Self::AdapterNotFound => write!(f, "Failed to find appropriate adapter"),
+            Self::MissingFeatures(features) => {
+                write!(f, "Adapter is missing required features: {features:?}")
+            }
+            Self::UnsupportedLimits(limits) => {
+                write!(f, "Adapter does not support required limits:")?;
+                for (name, requested, allowed) in limits {
+                    write!(f, " {name} (requested {requested}, allowed {allowed})")?;
+                }
+                Ok(())
+            }
+            Self::DeviceRequest(err) => write!(f, "Failed to create device: {err}"),
+            Self::UnsupportedSurface => write!(f, "Surface is not supported by the adapter"),
+            Self::Validation {
+                label,
+                location,
+                message,
+            } => write!(
+                f,
+                "Validation error creating '{label}' at {location}: {message}"
+            ),
+            Self::SurfaceTexture(err) => write!(f, "Failed to acquire surface texture: {err}"),
+            Self::UnknownBuffer(label) => write!(f, "No buffer labelled '{label}'"),
+            Self::UnknownKernel(label) => write!(f, "No kernel labelled '{label}'"),
+            Self::ShaderReflection { label, message } => {
+                write!(f, "Failed to reflect shader '{label}': {message}")
+            }
+            Self::GraphCycle(passes) => write!(
+                f,
+                "Compute graph passes depend on each other: {}",
+                passes.join(", ")
+            ),
+            Self::DimensionMismatch(message) => write!(f, "Mismatched dimensions: {message}"),
+            Self::AliasedBuffers(message) => write!(f, "Buffers must be distinct: {message}"),
+            Self::UnsupportedFftLength(length) => {
+                write!(f, "FFT length {length} is not supported")
+            }
+            Self::VerificationFailed(summary) => {
+                write!(f, "GPU results differ from the CPU reference: {summary}")
+            }
+            Self::BufferRead(err) => write!(f, "Failed to read buffer: {err}"),
+            Self::InvalidBufferRange { label, message } => {
+                write!(f, "Invalid range for buffer '{label}': {message}")
+            }
+            Self::UnsupportedTextureFormat(format) => {
+                write!(f, "Texture format {format:?} is not supported")
+            }
+            Self::Image(err) => write!(f, "Failed to load or save image: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for FrameworkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::EventLoop(err) => Some(err),
+            Self::WindowCreation(err) => Some(err),
+            Self::SurfaceCreation(err) => Some(err),
+            Self::DeviceRequest(err) => Some(err),
+            Self::SurfaceTexture(err) => Some(err),
+            Self::BufferRead(err) => Some(err),
+            Self::Image(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<EventLoopError> for FrameworkError {
+    fn from(err: EventLoopError) -> Self {
+        Self::EventLoop(err)
+    }
+}
+
+impl From<OsError> for FrameworkError {
+    fn from(err: OsError) -> Self {
+        Self::WindowCreation(err)
+    }
+}
+
+impl From<CreateSurfaceError> for FrameworkError {
+    fn from(err: CreateSurfaceError) -> Self {
+        Self::SurfaceCreation(err)
+    }
+}
+
+impl From<RequestDeviceError> for FrameworkError {
+    fn from(err: RequestDeviceError) -> Self {
+        Self::DeviceRequest(err)
+    }
+}
+
+impl From<SurfaceError> for FrameworkError {
+    fn from(err: SurfaceError) -> Self {
+        Self::SurfaceTexture(err)
+    }
+}
+
+impl From<BufferAsyncError> for FrameworkError {
+    fn from(err: BufferAsyncError) -> Self {
+        Self::BufferRead(err)
+    }
+}
+
+impl From<image::ImageError> for FrameworkError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    f64::consts::TAU,
+    ops::{Add, Mul, Sub},
+};
+
+use bytemuck::{Pod, Zeroable};
+
+use super::transform::{FftDirection, FftNormalization};
+
+// Complex number laid out as a WGSL vec2<f32>
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Complex {
+    pub re: f32,
+    pub im: f32,
+}
+
+impl Complex {
+    pub const ZERO: Self = Self { re: 0.0, im: 0.0 };
+
+    pub fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    // Unit complex number exp(i * angle)
+    pub fn from_angle(angle: f32) -> Self {
+        Self {
+            re: angle.cos(),
+            im: angle.sin(),
+        }
+    }
+
+    pub fn conj(self) -> Self {
+        Self {
+            re: self.re,
+            im: -self.im,
+        }
+    }
+
+    pub fn norm(self) -> f32 {
+        self.re.hypot(self.im)
+    }
+
+    pub fn scale(self, factor: f32) -> Self {
+        Self {
+            re: self.re * factor,
+            im: self.im * factor,
+        }
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+// Reference implementations, direct O(n^2) DFTs accumulated in f64
+// Transforms every consecutive run of `length` elements
+pub fn cpu_dft(
+    input: &[Complex],
+    length: usize,
+    direction: FftDirection,
+    normalization: FftNormalization,
+) -> Vec<Complex> {
+    if length == 0 {
+        return Vec::new();
+    }
+    let scale: f64 = normalization.scale(length, direction) as f64;
+    input
+        .chunks_exact(length)
+        .flat_map(|signal| dft(signal, direction, scale))
+        .collect()
+}
+
+// Transforms a row-major width x height grid, rows first and then columns
+pub fn cpu_dft_2d(
+    input: &[Complex],
+    width: usize,
+    height: usize,
+    direction: FftDirection,
+    normalization: FftNormalization,
+) -> Vec<Complex> {
+    let rows: Vec<Complex> = cpu_dft(input, width, direction, FftNormalization::None);
+    let mut output: Vec<Complex> = vec![Complex::ZERO; width * height];
+    let scale: f64 = normalization.scale(width * height, direction) as f64;
+    for column in 0..width {
+        let signal: Vec<Complex> = (0..height).map(|row| rows[row * width + column]).collect();
+        for (row, value) in dft(&signal, direction, scale).into_iter().enumerate() {
+            output[row * width + column] = value;
+        }
+    }
+    output
+}
+
+fn dft(signal: &[Complex], direction: FftDirection, scale: f64) -> Vec<Complex> {
+    let length: usize = signal.len();
+    let sign: f64 = direction.sign() as f64;
+    (0..length)
+        .map(|frequency| {
+            let (mut re, mut im): (f64, f64) = (0.0, 0.0);
+            for (index, value) in signal.iter().enumerate() {
+                // Reduced modulo the length first so the angle stays precise
+                let angle: f64 = sign * TAU * ((index * frequency) % length) as f64 / length as f64;
+                let (sin, cos): (f64, f64) = angle.sin_cos();
+                re += value.re as f64 * cos - value.im as f64 * sin;
+                im += value.re as f64 * sin + value.im as f64 * cos;
+            }
+            Complex::new((re * scale) as f32, (im * scale) as f32)
+        })
+        .collect()
+}
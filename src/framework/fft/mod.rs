@@ -0,0 +1,2 @@
+pub mod complex;
+pub mod transform;
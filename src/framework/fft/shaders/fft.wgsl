@@ -0,0 +1,74 @@
+// One pass of a mixed-radix Stockham FFT, each invocation computes one radix-point DFT
+// (butterfly) of one transform in the batch
+// -> Reads the inputs `length / radix` elements apart, multiplied by their twiddle factors,
+//    and writes the outputs `span` elements apart, where span is the product of the radices
+//    of the previous passes, which sorts the outputs without a bit reversal pass
+const WORKGROUP_SIZE: u32 = 64u;
+const MAX_RADIX: u32 = 16u;
+const TAU: f32 = 6.283185307179586;
+
+struct Params {
+    // Elements per transform
+    length: u32,
+    radix: u32,
+    span: u32,
+    // Distance between consecutive elements of a transform, and between transforms
+    element_stride: u32,
+    batch_stride: u32,
+    batch_count: u32,
+    // -1 for forward transforms, 1 for inverse transforms
+    sign: f32,
+    // Applied to the outputs, normalisation on the last pass
+    scale: f32,
+}
+
+@group(0) @binding(0)
+var<storage, read> src_data: array<vec2<f32>>;
+
+@group(0) @binding(1)
+var<storage, read_write> dst_data: array<vec2<f32>>;
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+fn complex_multiply(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+// exp(sign * 2πi * numerator / denominator), reduced first so the angle stays precise
+fn twiddle(numerator: u32, denominator: u32) -> vec2<f32> {
+    let angle: f32 = params.sign * TAU * f32(numerator % denominator) / f32(denominator);
+    return vec2<f32>(cos(angle), sin(angle));
+}
+
+@compute @workgroup_size(WORKGROUP_SIZE)
+fn fft_pass(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let butterflies: u32 = params.length / params.radix;
+    let index: u32 = id.x + (id.y + id.z * num_workgroups.y) * num_workgroups.x * WORKGROUP_SIZE;
+    if index >= butterflies * params.batch_count {
+        return;
+    }
+    let batch: u32 = index / butterflies;
+    let butterfly: u32 = index % butterflies;
+    let base: u32 = batch * params.batch_stride;
+    // Position within the sub-transforms of the previous passes
+    let offset: u32 = butterfly % params.span;
+
+    var values: array<vec2<f32>, MAX_RADIX>;
+    for (var r: u32 = 0u; r < params.radix; r++) {
+        let value: vec2<f32> = src_data[base + (butterfly + r * butterflies) * params.element_stride];
+        values[r] = complex_multiply(value, twiddle(offset * r, params.span * params.radix));
+    }
+
+    let first_output: u32 = (butterfly - offset) * params.radix + offset;
+    for (var k: u32 = 0u; k < params.radix; k++) {
+        var sum: vec2<f32> = vec2<f32>(0.0);
+        for (var r: u32 = 0u; r < params.radix; r++) {
+            sum += complex_multiply(values[r], twiddle(r * k, params.radix));
+        }
+        dst_data[base + (first_output + k * params.span) * params.element_stride] = sum * params.scale;
+    }
+}
@@ -0,0 +1,520 @@
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    BindGroup, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor,
+    ComputePassDescriptor, Device, Queue,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        compute_kernel::ComputeKernel,
+        gpu_buffer::GpuBuffer,
+        utilities::{create_buffer, create_empty_buffer},
+    },
+};
+
+use super::complex::Complex;
+
+// Largest radix of a single pass, must match the shader
+const MAX_RADIX: u32 = 16;
+// Radices tried in order when factoring a length, larger radices mean fewer passes
+const RADICES: [u32; 7] = [4, 2, 3, 5, 7, 11, 13];
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    length: u32,
+    radix: u32,
+    span: u32,
+    element_stride: u32,
+    batch_stride: u32,
+    batch_count: u32,
+    sign: f32,
+    scale: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FftDirection {
+    // exp(-2πi jk/n)
+    Forward,
+    // exp(+2πi jk/n)
+    Inverse,
+}
+
+impl FftDirection {
+    pub fn sign(self) -> f32 {
+        match self {
+            Self::Forward => -1.0,
+            Self::Inverse => 1.0,
+        }
+    }
+}
+
+// Scaling of the outputs of an n point transform, named after numpy.fft's norm modes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FftNormalization {
+    None,
+    // 1/n on inverse transforms, so that inverse(forward(x)) == x
+    #[default]
+    Backward,
+    // 1/sqrt(n) in both directions
+    Orthonormal,
+    // 1/n on forward transforms
+    Forward,
+}
+
+impl FftNormalization {
+    pub fn scale(self, n: usize, direction: FftDirection) -> f32 {
+        match (self, direction) {
+            (Self::Backward, FftDirection::Inverse) | (Self::Forward, FftDirection::Forward) => {
+                1.0 / n as f32
+            }
+            (Self::Orthonormal, _) => 1.0 / (n as f32).sqrt(),
+            _ => 1.0,
+        }
+    }
+}
+
+// Batch of transforms of `length` elements, `element_stride` apart within a transform
+// and `batch_stride` apart between transforms
+#[derive(Clone, Copy)]
+struct Stage {
+    length: u32,
+    element_stride: u32,
+    batch_stride: u32,
+    batch_count: u32,
+}
+
+// Mixed-radix complex FFT, in place on storage buffers of Complex
+// -> Lengths must factor into primes up to 13, each pass runs one radix (Stockham
+//    autosort) alternating with a scratch buffer, so no bit reversal is needed
+// -> 2D transforms run over the rows of row-major data, then over its columns
+// -> Twiddle factors are computed in f32 on the fly, expect errors of about
+//    1e-6 * log2(n) relative to the largest output
+pub struct Fft {
+    normalization: FftNormalization,
+    kernel: ComputeKernel,
+}
+
+impl Fft {
+    #[track_caller]
+    pub fn new(device: &Device) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            normalization: FftNormalization::default(),
+            kernel: ComputeKernel::builder(include_str!("shaders/fft.wgsl"), "fft_pass")
+                .with_label("fft_pass")
+                .build(device)?,
+        })
+    }
+
+    pub fn with_normalization(mut self, normalization: FftNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    pub fn normalization(&self) -> FftNormalization {
+        self.normalization
+    }
+
+    // Whether transforms of this length are supported
+    pub fn supports_length(length: u32) -> bool {
+        factor_length(length).is_ok()
+    }
+
+    // <---- Transforms ---->
+    // Transforms every consecutive run of `length` elements
+    #[track_caller]
+    pub fn transform(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        data: &GpuBuffer<Complex>,
+        length: u32,
+        direction: FftDirection,
+    ) -> Result<(), FrameworkError> {
+        if length == 0 || !data.len().is_multiple_of(length as usize) {
+            return Err(FrameworkError::DimensionMismatch(format!(
+                "FFT of length {length} over a buffer of {} elements",
+                data.len()
+            )));
+        }
+        let batch_count: u32 = (data.len() / length as usize) as u32;
+        self.submit(device, queue, |encoder| {
+            self.record(
+                device,
+                encoder,
+                data.buffer(),
+                length,
+                batch_count,
+                direction,
+            )
+        })
+    }
+
+    // Transforms a row-major width x height grid
+    #[track_caller]
+    pub fn transform_2d(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        data: &GpuBuffer<Complex>,
+        width: u32,
+        height: u32,
+        direction: FftDirection,
+    ) -> Result<(), FrameworkError> {
+        if data.len() != width as usize * height as usize {
+            return Err(FrameworkError::DimensionMismatch(format!(
+                "2D FFT of {width}x{height} over a buffer of {} elements",
+                data.len()
+            )));
+        }
+        self.submit(device, queue, |encoder| {
+            self.record_2d(device, encoder, data.buffer(), width, height, direction)
+        })
+    }
+
+    fn submit(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        record: impl FnOnce(&mut CommandEncoder) -> Result<(), FrameworkError>,
+    ) -> Result<(), FrameworkError> {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("fft_encoder"),
+            });
+        record(&mut encoder)?;
+        queue.submit(Some(encoder.finish()));
+        Ok(())
+    }
+
+    // <---- Recording ---->
+    // Transforms `batch_count` consecutive signals of `length` elements in place,
+    // the buffer must have the STORAGE and COPY_DST usages
+    #[track_caller]
+    pub fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        data: &Buffer,
+        length: u32,
+        batch_count: u32,
+        direction: FftDirection,
+    ) -> Result<(), FrameworkError> {
+        let stage: Stage = Stage {
+            length,
+            element_stride: 1,
+            batch_stride: length,
+            batch_count,
+        };
+        self.record_stages(device, encoder, data, &[stage], direction)
+    }
+
+    #[track_caller]
+    pub fn record_2d(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        data: &Buffer,
+        width: u32,
+        height: u32,
+        direction: FftDirection,
+    ) -> Result<(), FrameworkError> {
+        let rows: Stage = Stage {
+            length: width,
+            element_stride: 1,
+            batch_stride: width,
+            batch_count: height,
+        };
+        let columns: Stage = Stage {
+            length: height,
+            element_stride: width,
+            batch_stride: 1,
+            batch_count: width,
+        };
+        self.record_stages(device, encoder, data, &[rows, columns], direction)
+    }
+
+    // Every stage covers all elements, so passes of consecutive stages keep alternating
+    // between the data and scratch buffers
+    #[track_caller]
+    fn record_stages(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        data: &Buffer,
+        stages: &[Stage],
+        direction: FftDirection,
+    ) -> Result<(), FrameworkError> {
+        let count: u32 = stages[0].length * stages[0].batch_count;
+        if count == 0 {
+            return Ok(());
+        }
+        let mut passes: Vec<Params> = Vec::new();
+        for stage in stages {
+            let mut span: u32 = 1;
+            for radix in factor_length(stage.length)? {
+                passes.push(Params {
+                    length: stage.length,
+                    radix,
+                    span,
+                    element_stride: stage.element_stride,
+                    batch_stride: stage.batch_stride,
+                    batch_count: stage.batch_count,
+                    sign: direction.sign(),
+                    scale: 1.0,
+                });
+                span *= radix;
+            }
+        }
+        // Normalisation is folded into the last pass
+        let n_points: usize = stages.iter().map(|stage| stage.length as usize).product();
+        if let Some(last) = passes.last_mut() {
+            last.scale = self.normalization.scale(n_points, direction);
+        }
+
+        let size: u64 = (count as usize * size_of::<Complex>()) as u64;
+        let scratch: Buffer = create_empty_buffer(
+            device,
+            size,
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            Some("fft_scratch"),
+        )?;
+        for (index, params) in passes.iter().enumerate() {
+            let (src, dst): (&Buffer, &Buffer) = match index % 2 == 0 {
+                true => (data, &scratch),
+                false => (&scratch, data),
+            };
+            let params_buffer: Buffer = create_buffer(
+                device,
+                bytemuck::bytes_of(params),
+                BufferUsages::UNIFORM,
+                Some("fft_params"),
+            )?;
+            let bind_group: BindGroup = self
+                .kernel
+                .create_bind_group(device, &[(0, src), (1, dst), (2, &params_buffer)])?;
+            let butterflies: u32 = params.length / params.radix * params.batch_count;
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("fft_pass"),
+                timestamp_writes: None,
+            });
+            self.kernel.dispatch_with(
+                &mut compute_pass,
+                &bind_group,
+                self.kernel.workgroups_for(butterflies),
+            );
+        }
+
+        // An odd number of passes leaves the result in the scratch buffer
+        if passes.len() % 2 == 1 {
+            encoder.copy_buffer_to_buffer(&scratch, 0, data, 0, size);
+        }
+        Ok(())
+    }
+}
+
+// Radices of the passes transforming `length` elements, a single radix 1 pass for length 1
+// so that normalisation and the copy back still happen
+fn factor_length(length: u32) -> Result<Vec<u32>, FrameworkError> {
+    if length == 0 {
+        return Err(FrameworkError::UnsupportedFftLength(length));
+    }
+    if length == 1 {
+        return Ok(vec![1]);
+    }
+    let mut radices: Vec<u32> = Vec::new();
+    let mut remaining: u32 = length;
+    for radix in RADICES {
+        debug_assert!(radix <= MAX_RADIX);
+        while remaining.is_multiple_of(radix) {
+            radices.push(radix);
+            remaining /= radix;
+        }
+    }
+    match remaining {
+        1 => Ok(radices),
+        _ => Err(FrameworkError::UnsupportedFftLength(length)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        fft::complex::{cpu_dft, cpu_dft_2d, Complex},
+        gpu::{gpu_buffer::GpuBuffer, gpu_context::GPUContext},
+        verification::{
+            compare::{compare, Tolerance},
+            kernel_check::uniform_f32,
+            verification_context,
+        },
+    };
+
+    use super::{Fft, FftDirection, FftNormalization};
+
+    fn random_signal(seed: u64, len: usize) -> Vec<Complex> {
+        uniform_f32(seed, 2 * len, -1.0..1.0)
+            .chunks_exact(2)
+            .map(|pair| Complex::new(pair[0], pair[1]))
+            .collect()
+    }
+
+    // Errors grow with log2(n) and with the largest output (see Fft)
+    fn check(actual: &[Complex], expected: &[Complex], n: usize) -> Result<(), FrameworkError> {
+        let largest: f32 = expected
+            .iter()
+            .map(|value| value.norm())
+            .fold(1.0, f32::max);
+        let absolute: f64 = 4e-6 * (n as f64).log2().max(1.0) * largest as f64;
+        compare(actual, expected, Tolerance::new().with_absolute(absolute)).into_result()
+    }
+
+    fn gpu_transform(
+        context: &GPUContext,
+        fft: &Fft,
+        signal: &[Complex],
+        length: u32,
+        direction: FftDirection,
+    ) -> Result<Vec<Complex>, FrameworkError> {
+        let data: GpuBuffer<Complex> = GpuBuffer::storage(&context.device, signal, Some("fft"))?;
+        fft.transform(&context.device, &context.queue, &data, length, direction)?;
+        data.read(&context.device, &context.queue)
+    }
+
+    #[test]
+    fn transforms_match_dft() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let fft: Fft = Fft::new(&context.device)?;
+        // Length 1, radix 2 (odd and even pass counts), 3 and 5, then mixed radices
+        let lengths: [u32; 11] = [
+            1,
+            8,
+            32,
+            1024,
+            27,
+            81,
+            25,
+            125,
+            60,
+            1001,
+            2 * 3 * 5 * 7 * 13,
+        ];
+        for length in lengths {
+            // Batches of 3 signals
+            let signal: Vec<Complex> = random_signal(length as u64, 3 * length as usize);
+            for direction in [FftDirection::Forward, FftDirection::Inverse] {
+                let actual: Vec<Complex> =
+                    gpu_transform(&context, &fft, &signal, length, direction)?;
+                let expected: Vec<Complex> = cpu_dft(
+                    &signal,
+                    length as usize,
+                    direction,
+                    FftNormalization::Backward,
+                );
+                check(&actual, &expected, length as usize)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn normalizations_match_dft() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let length: u32 = 48;
+        let signal: Vec<Complex> = random_signal(1, 2 * length as usize);
+        for normalization in [
+            FftNormalization::None,
+            FftNormalization::Backward,
+            FftNormalization::Orthonormal,
+            FftNormalization::Forward,
+        ] {
+            let fft: Fft = Fft::new(&context.device)?.with_normalization(normalization);
+            for direction in [FftDirection::Forward, FftDirection::Inverse] {
+                let actual: Vec<Complex> =
+                    gpu_transform(&context, &fft, &signal, length, direction)?;
+                let expected: Vec<Complex> =
+                    cpu_dft(&signal, length as usize, direction, normalization);
+                check(&actual, &expected, length as usize)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn inverse_round_trip() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        for normalization in [FftNormalization::Backward, FftNormalization::Orthonormal] {
+            let fft: Fft = Fft::new(&context.device)?.with_normalization(normalization);
+            let signal: Vec<Complex> = random_signal(2, 4 * 360);
+            let data: GpuBuffer<Complex> =
+                GpuBuffer::storage(&context.device, &signal, Some("fft"))?;
+            fft.transform(
+                &context.device,
+                &context.queue,
+                &data,
+                360,
+                FftDirection::Forward,
+            )?;
+            fft.transform(
+                &context.device,
+                &context.queue,
+                &data,
+                360,
+                FftDirection::Inverse,
+            )?;
+            check(&data.read(&context.device, &context.queue)?, &signal, 360)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn transforms_2d_match_dft() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let fft: Fft = Fft::new(&context.device)?;
+        for (width, height) in [(1, 1), (8, 8), (16, 9), (5, 12), (1, 30)] {
+            let signal: Vec<Complex> = random_signal(3, (width * height) as usize);
+            for direction in [FftDirection::Forward, FftDirection::Inverse] {
+                let data: GpuBuffer<Complex> =
+                    GpuBuffer::storage(&context.device, &signal, Some("fft_2d"))?;
+                fft.transform_2d(
+                    &context.device,
+                    &context.queue,
+                    &data,
+                    width,
+                    height,
+                    direction,
+                )?;
+                let expected: Vec<Complex> = cpu_dft_2d(
+                    &signal,
+                    width as usize,
+                    height as usize,
+                    direction,
+                    FftNormalization::Backward,
+                );
+                let n: usize = (width * height) as usize;
+                check(&data.read(&context.device, &context.queue)?, &expected, n)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_lengths_are_rejected() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let fft: Fft = Fft::new(&context.device)?;
+        assert!(!Fft::supports_length(17 * 2));
+        let signal: Vec<Complex> = random_signal(4, 34);
+        assert!(matches!(
+            gpu_transform(&context, &fft, &signal, 34, FftDirection::Forward),
+            Err(FrameworkError::UnsupportedFftLength(_))
+        ));
+        assert!(matches!(
+            gpu_transform(&context, &fft, &signal, 10, FftDirection::Forward),
+            Err(FrameworkError::DimensionMismatch(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,350 @@
+use std::env;
+
+use wgpu::{
+    Adapter, AdapterInfo, Backends, DeviceType, Instance, InstanceDescriptor, PowerPreference,
+    RequestAdapterOptions, Surface,
+};
+
+// Environment variables overriding the selection policy
+// -> WGPU_BACKEND and WGPU_POWER_PREF follow wgpu's own conventions
+pub const ADAPTER_NAME_ENV: &str = "WGPU_ADAPTER_NAME";
+pub const ADAPTER_VENDOR_ENV: &str = "WGPU_ADAPTER_VENDOR";
+pub const DEVICE_TYPE_ENV: &str = "WGPU_DEVICE_TYPE";
+pub const FORCE_FALLBACK_ADAPTER_ENV: &str = "WGPU_FORCE_FALLBACK_ADAPTER";
+
+#[derive(Clone, Debug)]
+pub struct AdapterSelection {
+    pub backends: Backends,
+    pub power_preference: PowerPreference,
+    // Only accept software adapters (llvmpipe, lavapipe, WARP...)
+    pub force_fallback_adapter: bool,
+    // Case-insensitive substring of the adapter name
+    pub name_filter: Option<String>,
+    // PCI vendor ID
+    pub vendor: Option<u32>,
+    pub device_type: Option<DeviceType>,
+}
+
+impl Default for AdapterSelection {
+    fn default() -> Self {
+        Self {
+            backends: Backends::all(),
+            power_preference: PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+            name_filter: None,
+            vendor: None,
+            device_type: None,
+        }
+    }
+}
+
+impl AdapterSelection {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_env() -> Self {
+        Self::new().with_env_overrides()
+    }
+
+    // <---- Builder ---->
+    pub fn with_backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    pub fn with_power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    pub fn with_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = force_fallback_adapter;
+        self
+    }
+
+    pub fn with_name_filter(mut self, name: &str) -> Self {
+        self.name_filter = Some(name.to_owned());
+        self
+    }
+
+    pub fn with_vendor(mut self, vendor: u32) -> Self {
+        self.vendor = Some(vendor);
+        self
+    }
+
+    pub fn with_device_type(mut self, device_type: DeviceType) -> Self {
+        self.device_type = Some(device_type);
+        self
+    }
+
+    pub fn with_env_overrides(mut self) -> Self {
+        if let Some(backends) = wgpu::util::backend_bits_from_env() {
+            self.backends = backends;
+        }
+        if let Some(power_preference) = wgpu::util::power_preference_from_env() {
+            self.power_preference = power_preference;
+        }
+        self.with_overrides(|name| env::var(name).ok())
+    }
+
+    // Overrides read from the framework's variables, by name
+    fn with_overrides(mut self, variable: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(name) = variable(ADAPTER_NAME_ENV) {
+            self.name_filter = Some(name);
+        }
+        if let Some(vendor) = variable(ADAPTER_VENDOR_ENV) {
+            match parse_vendor(&vendor) {
+                Some(vendor) => self.vendor = Some(vendor),
+                None => log::warn!("Ignoring invalid {ADAPTER_VENDOR_ENV}: {vendor}"),
+            }
+        }
+        if let Some(device_type) = variable(DEVICE_TYPE_ENV) {
+            match parse_device_type(&device_type) {
+                Some(device_type) => self.device_type = Some(device_type),
+                None => log::warn!("Ignoring invalid {DEVICE_TYPE_ENV}: {device_type}"),
+            }
+        }
+        if let Some(force_fallback_adapter) = variable(FORCE_FALLBACK_ADAPTER_ENV) {
+            self.force_fallback_adapter = matches!(
+                force_fallback_adapter.trim().to_lowercase().as_str(),
+                "1" | "true"
+            );
+        }
+        self
+    }
+
+    // <---- Selection ---->
+    pub fn create_instance(&self) -> Instance {
+        Instance::new(InstanceDescriptor {
+            backends: self.backends,
+            ..Default::default()
+        })
+    }
+
+    pub async fn select_adapter(
+        &self,
+        instance: &Instance,
+        compatible_surface: Option<&Surface<'_>>,
+    ) -> Option<Adapter> {
+        // Pick the best matching adapter among those enumerated
+        let mut adapters: Vec<Adapter> = instance
+            .enumerate_adapters(self.backends)
+            .into_iter()
+            .filter(|adapter| {
+                compatible_surface.is_none_or(|surface| adapter.is_surface_supported(surface))
+            })
+            .filter(|adapter| self.matches(&adapter.get_info()))
+            .collect();
+        adapters.sort_by_key(|adapter| self.device_type_rank(adapter.get_info().device_type));
+        if let Some(adapter) = adapters.into_iter().next() {
+            return Some(adapter);
+        }
+
+        // Let wgpu decide when nothing could be enumerated and no filter was requested
+        if self.name_filter.is_none() && self.vendor.is_none() && self.device_type.is_none() {
+            return instance
+                .request_adapter(&RequestAdapterOptions {
+                    power_preference: self.power_preference,
+                    force_fallback_adapter: self.force_fallback_adapter,
+                    compatible_surface,
+                })
+                .await;
+        }
+        None
+    }
+
+    pub fn matches(&self, info: &AdapterInfo) -> bool {
+        let fallback_ok: bool = !self.force_fallback_adapter || info.device_type == DeviceType::Cpu;
+        let name_ok: bool = self
+            .name_filter
+            .as_ref()
+            .is_none_or(|name| info.name.to_lowercase().contains(&name.to_lowercase()));
+        let vendor_ok: bool = self.vendor.is_none_or(|vendor| info.vendor == vendor);
+        let device_type_ok: bool = self
+            .device_type
+            .is_none_or(|device_type| info.device_type == device_type);
+        fallback_ok && name_ok && vendor_ok && device_type_ok
+    }
+
+    // Lower is better
+    fn device_type_rank(&self, device_type: DeviceType) -> u8 {
+        match (self.power_preference, device_type) {
+            (PowerPreference::LowPower, DeviceType::IntegratedGpu) => 0,
+            (PowerPreference::LowPower, DeviceType::DiscreteGpu) => 1,
+            (_, DeviceType::DiscreteGpu) => 0,
+            (_, DeviceType::IntegratedGpu) => 1,
+            (_, DeviceType::VirtualGpu) => 2,
+            (_, DeviceType::Cpu) => 3,
+            (_, DeviceType::Other) => 4,
+        }
+    }
+}
+
+fn parse_vendor(vendor: &str) -> Option<u32> {
+    let vendor: &str = vendor.trim();
+    match vendor
+        .strip_prefix("0x")
+        .or_else(|| vendor.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => vendor.parse().ok(),
+    }
+}
+
+fn parse_device_type(device_type: &str) -> Option<DeviceType> {
+    match device_type.trim().to_lowercase().as_str() {
+        "discrete" | "discretegpu" => Some(DeviceType::DiscreteGpu),
+        "integrated" | "integratedgpu" => Some(DeviceType::IntegratedGpu),
+        "virtual" | "virtualgpu" => Some(DeviceType::VirtualGpu),
+        "cpu" | "software" => Some(DeviceType::Cpu),
+        "other" => Some(DeviceType::Other),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{AdapterInfo, Backend, DeviceType};
+
+    use super::{
+        parse_device_type, parse_vendor, AdapterSelection, ADAPTER_NAME_ENV, ADAPTER_VENDOR_ENV,
+        DEVICE_TYPE_ENV, FORCE_FALLBACK_ADAPTER_ENV,
+    };
+
+    fn adapter(name: &str, vendor: u32, device_type: DeviceType) -> AdapterInfo {
+        AdapterInfo {
+            name: name.to_owned(),
+            vendor,
+            device: 0,
+            device_type,
+            driver: String::new(),
+            driver_info: String::new(),
+            backend: Backend::Vulkan,
+        }
+    }
+
+    // Selection with the given variables set, and no others
+    fn with_variables(variables: &[(&str, &str)]) -> AdapterSelection {
+        AdapterSelection::new().with_overrides(|name| {
+            variables
+                .iter()
+                .find(|(variable, _)| *variable == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn vendors() {
+        for (value, vendor) in [
+            ("4318", Some(0x10DE)),
+            ("0x10de", Some(0x10DE)),
+            ("0x10DE", Some(0x10DE)),
+            ("0X1002", Some(0x1002)),
+            (" 0x8086 ", Some(0x8086)),
+            ("0", Some(0)),
+            ("", None),
+            ("0x", None),
+            ("nvidia", None),
+            ("10de", None),
+            ("-1", None),
+            ("0x1_0000_0000", None),
+        ] {
+            assert_eq!(parse_vendor(value), vendor, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn device_types() {
+        for (value, device_type) in [
+            ("discrete", Some(DeviceType::DiscreteGpu)),
+            ("DiscreteGpu", Some(DeviceType::DiscreteGpu)),
+            ("INTEGRATED", Some(DeviceType::IntegratedGpu)),
+            ("virtualgpu", Some(DeviceType::VirtualGpu)),
+            (" cpu ", Some(DeviceType::Cpu)),
+            ("Software", Some(DeviceType::Cpu)),
+            ("other", Some(DeviceType::Other)),
+            ("gpu", None),
+            ("", None),
+        ] {
+            assert_eq!(parse_device_type(value), device_type, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn overrides() {
+        let selection: AdapterSelection = with_variables(&[
+            (ADAPTER_NAME_ENV, "GeForce"),
+            (ADAPTER_VENDOR_ENV, "0x10DE"),
+            (DEVICE_TYPE_ENV, "Discrete"),
+        ]);
+        assert_eq!(selection.name_filter.as_deref(), Some("GeForce"));
+        assert_eq!(selection.vendor, Some(0x10DE));
+        assert_eq!(selection.device_type, Some(DeviceType::DiscreteGpu));
+        assert!(!selection.force_fallback_adapter);
+
+        // Invalid values keep the earlier settings
+        let selection: AdapterSelection = AdapterSelection::new()
+            .with_vendor(0x1002)
+            .with_device_type(DeviceType::Cpu)
+            .with_overrides(|name| match name {
+                ADAPTER_VENDOR_ENV => Some("amd".to_owned()),
+                DEVICE_TYPE_ENV => Some("fast".to_owned()),
+                _ => None,
+            });
+        assert_eq!(selection.vendor, Some(0x1002));
+        assert_eq!(selection.device_type, Some(DeviceType::Cpu));
+
+        for (value, force_fallback_adapter) in [
+            ("1", true),
+            ("true", true),
+            ("TRUE", true),
+            ("0", false),
+            ("false", false),
+            ("yes", false),
+            ("", false),
+        ] {
+            // Overrides the builder in both directions
+            for initial in [false, true] {
+                let selection: AdapterSelection = AdapterSelection::new()
+                    .with_fallback_adapter(initial)
+                    .with_overrides(|name| {
+                        (name == FORCE_FALLBACK_ADAPTER_ENV).then(|| value.to_owned())
+                    });
+                assert_eq!(
+                    selection.force_fallback_adapter, force_fallback_adapter,
+                    "{value:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matching_adapters() {
+        let gpu: AdapterInfo = adapter("NVIDIA GeForce RTX 4090", 0x10DE, DeviceType::DiscreteGpu);
+        let software: AdapterInfo = adapter("llvmpipe (LLVM 17.0.6)", 0x10005, DeviceType::Cpu);
+
+        let any: AdapterSelection = AdapterSelection::new();
+        assert!(any.matches(&gpu) && any.matches(&software));
+
+        let by_name: AdapterSelection = with_variables(&[(ADAPTER_NAME_ENV, "geforce")]);
+        assert!(by_name.matches(&gpu) && !by_name.matches(&software));
+
+        let by_vendor: AdapterSelection = with_variables(&[(ADAPTER_VENDOR_ENV, "4318")]);
+        assert!(by_vendor.matches(&gpu) && !by_vendor.matches(&software));
+
+        // Fallback adapters only, combined with the other filters
+        let fallback: AdapterSelection = with_variables(&[(FORCE_FALLBACK_ADAPTER_ENV, "1")]);
+        assert!(!fallback.matches(&gpu) && fallback.matches(&software));
+        let conflicting: AdapterSelection = with_variables(&[
+            (FORCE_FALLBACK_ADAPTER_ENV, "true"),
+            (DEVICE_TYPE_ENV, "discrete"),
+        ]);
+        assert!(!conflicting.matches(&gpu) && !conflicting.matches(&software));
+        let named_fallback: AdapterSelection = with_variables(&[
+            (FORCE_FALLBACK_ADAPTER_ENV, "true"),
+            (ADAPTER_NAME_ENV, "LLVMPIPE"),
+        ]);
+        assert!(named_fallback.matches(&software));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// Environment variable enabling API trace recording into the given directory
+pub const TRACE_DIR_ENV: &str = "WGPU_TRACE";
+
+// Run directories created by this process, so that contexts created within the same second
+// record into separate directories
+static RUN_COUNT: AtomicU64 = AtomicU64::new(0);
+
+// Records every wgpu API call so that a run can be replayed with wgpu's player
+#[derive(Clone, Debug, Default)]
+pub struct ApiTrace {
+    // Parent directory, each run records into its own subdirectory
+    pub directory: Option<PathBuf>,
+}
+
+impl ApiTrace {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn from_env() -> Self {
+        Self {
+            directory: env::var_os(TRACE_DIR_ENV).map(PathBuf::from),
+        }
+    }
+
+    pub fn in_directory(directory: impl AsRef<Path>) -> Self {
+        Self {
+            directory: Some(directory.as_ref().to_path_buf()),
+        }
+    }
+
+    // Creates <directory>/trace-<unix time>-<process id>-<run> for this run
+    pub fn create_run_directory(&self) -> Option<PathBuf> {
+        let directory: &Path = self.directory.as_deref()?;
+        let timestamp: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let result: io::Result<PathBuf> = fs::create_dir_all(directory).and_then(|()| loop {
+            let run: u64 = RUN_COUNT.fetch_add(1, Ordering::Relaxed);
+            let run_directory: PathBuf =
+                directory.join(format!("trace-{timestamp}-{}-{run}", std::process::id()));
+            // Left over from an earlier process with the same id
+            match fs::create_dir(&run_directory) {
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                result => break result.map(|()| run_directory),
+            }
+        });
+        match result {
+            Ok(run_directory) => {
+                log::info!("Recording wgpu API trace to {}", run_directory.display());
+                Some(run_directory)
+            }
+            Err(err) => {
+                log::warn!(
+                    "Failed to create trace directory in {}, tracing disabled: {err}",
+                    directory.display()
+                );
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::ApiTrace;
+
+    #[test]
+    fn runs_record_into_separate_directories() {
+        let parent: PathBuf =
+            std::env::temp_dir().join(format!("api_trace_test-{}", std::process::id()));
+        let trace: ApiTrace = ApiTrace::in_directory(&parent);
+        let first: PathBuf = trace.create_run_directory().unwrap();
+        let second: PathBuf = trace.create_run_directory().unwrap();
+        assert_ne!(first, second);
+        assert!(first.is_dir() && second.is_dir());
+        assert!(first.starts_with(&parent));
+        fs::remove_dir_all(&parent).unwrap();
+
+        assert!(ApiTrace::new().create_run_directory().is_none());
+    }
+}
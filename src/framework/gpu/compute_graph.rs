@@ -0,0 +1,662 @@
+use std::{collections::BTreeSet, mem::size_of};
+
+use bytemuck::Pod;
+use rustc_hash::FxHashMap;
+use wgpu::{
+    BindGroup, Buffer, CommandEncoder, CommandEncoderDescriptor, ComputePass,
+    ComputePassDescriptor, Device, Id, Queue,
+};
+
+use crate::framework::error::FrameworkError;
+
+use super::{compute_kernel::ComputeKernel, indirect::DispatchIndirectArgs, readback::Readback};
+
+// Number of times each ping-pong pair was swapped, by buffer label
+type FlipMap = FxHashMap<&'static str, u64>;
+// Bind group along with the ids of its buffers, so that re-created buffers are rebound
+type CachedBindGroup = (Vec<Id<Buffer>>, BindGroup);
+// Buffers bound by a pass, along with a key identifying the ping-pong buffers chosen
+type ResolvedBindings<'a> = (u64, Vec<(u32, &'a Buffer)>);
+
+// Ping-pong swaps made by recorded passes, applied with ComputeGraph::apply_flips once the
+// encoder is submitted, so that a dropped encoder leaves the graph unchanged
+#[must_use]
+pub struct RecordedFlips {
+    flips: FlipMap,
+}
+
+// Sequence of compute passes run for a number of iterations
+// -> Passes are ordered so that writers of a buffer run before its readers,
+//    passes without dependencies between them keep the order they were added in
+// -> Ping-pong pairs carry data from one pass (or iteration) to the next: passes read
+//    the front buffer and write the back buffer, the pair is swapped after every pass
+//    writing it, so a single writer per iteration swaps it between iterations
+// -> Every iteration of a run is recorded into a single encoder and submitted once
+pub struct ComputeGraph {
+    buffers: FxHashMap<&'static str, GraphBuffer>,
+    passes: Vec<GraphPass>,
+    // Created on build, in execution order
+    kernels: Vec<ComputeKernel>,
+    // By pass index and ping-pong parity of its bindings
+    bind_groups: FxHashMap<(usize, u64), CachedBindGroup>,
+}
+
+enum GraphBuffer {
+    Single(Buffer),
+    PingPong { buffers: [Buffer; 2], flips: u64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    // Bound as read_write
+    Write,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum PassDispatch {
+    // One invocation per element, see ComputeKernel::workgroups_for
+    Elements(u32),
+    // One invocation per cell of a width x height grid
+    Grid(u32, u32),
+    Workgroups(u32, u32, u32),
+    // Workgroup counts read from the DispatchIndirectArgs at `index` of a graph buffer
+    // (created with the INDIRECT usage), so that earlier passes can size the dispatch
+    Indirect { buffer: &'static str, index: u32 },
+}
+
+// Compute pass declaring the buffers it reads and writes
+pub struct GraphPass {
+    label: &'static str,
+    source: String,
+    entry_point: String,
+    bindings: Vec<(u32, &'static str, Access)>,
+    dispatch: PassDispatch,
+}
+
+impl GraphPass {
+    pub fn new(label: &'static str, source: &str, entry_point: &str) -> Self {
+        Self {
+            label,
+            source: source.to_owned(),
+            entry_point: entry_point.to_owned(),
+            bindings: Vec::new(),
+            dispatch: PassDispatch::Workgroups(1, 1, 1),
+        }
+    }
+
+    pub fn with_read(mut self, binding: u32, buffer: &'static str) -> Self {
+        self.bindings.push((binding, buffer, Access::Read));
+        self
+    }
+
+    pub fn with_write(mut self, binding: u32, buffer: &'static str) -> Self {
+        self.bindings.push((binding, buffer, Access::Write));
+        self
+    }
+
+    pub fn with_dispatch(mut self, dispatch: PassDispatch) -> Self {
+        self.dispatch = dispatch;
+        self
+    }
+
+    pub fn with_elements(self, n_elements: u32) -> Self {
+        self.with_dispatch(PassDispatch::Elements(n_elements))
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    // Reading indirect arguments orders the pass after their writers, like reading a binding
+    fn reads(&self, buffer: &str) -> bool {
+        let reads_args: bool =
+            matches!(self.dispatch, PassDispatch::Indirect { buffer: args, .. } if args == buffer);
+        reads_args
+            || self
+                .bindings
+                .iter()
+                .any(|(_, label, access)| *label == buffer && *access == Access::Read)
+    }
+
+    fn writes(&self, buffer: &str) -> bool {
+        self.bindings
+            .iter()
+            .any(|(_, label, access)| *label == buffer && *access == Access::Write)
+    }
+}
+
+impl Default for ComputeGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ComputeGraph {
+    pub fn new() -> Self {
+        Self {
+            buffers: Default::default(),
+            passes: Vec::new(),
+            kernels: Vec::new(),
+            bind_groups: Default::default(),
+        }
+    }
+
+    // <---- Buffers ---->
+    // Replaces any buffer with the same label
+    pub fn add_buffer(&mut self, label: &'static str, buffer: Buffer) {
+        self.buffers.insert(label, GraphBuffer::Single(buffer));
+        self.bind_groups.clear();
+    }
+
+    // `front` holds the initial contents
+    pub fn add_ping_pong(&mut self, label: &'static str, front: Buffer, back: Buffer) {
+        self.buffers.insert(
+            label,
+            GraphBuffer::PingPong {
+                buffers: [front, back],
+                flips: 0,
+            },
+        );
+        self.bind_groups.clear();
+    }
+
+    // Latest contents, i.e. the front buffer of ping-pong pairs
+    pub fn buffer(&self, label: &str) -> Result<&Buffer, FrameworkError> {
+        match self.graph_buffer(label)? {
+            GraphBuffer::Single(buffer) => Ok(buffer),
+            GraphBuffer::PingPong { buffers, flips } => Ok(&buffers[(flips % 2) as usize]),
+        }
+    }
+
+    // Blocks until the latest contents are available
+    #[track_caller]
+    pub fn read<T: Pod>(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        label: &str,
+    ) -> Result<Vec<T>, FrameworkError> {
+        Readback::buffer(device, queue, self.buffer(label)?)?.wait(device)
+    }
+
+    fn graph_buffer(&self, label: &str) -> Result<&GraphBuffer, FrameworkError> {
+        self.buffers
+            .get(label)
+            .ok_or_else(|| FrameworkError::UnknownBuffer(label.to_owned()))
+    }
+
+    // <---- Passes ---->
+    pub fn add_pass(&mut self, pass: GraphPass) {
+        self.passes.push(pass);
+        self.kernels.clear();
+        self.bind_groups.clear();
+    }
+
+    // Pass labels in execution order, once built
+    pub fn order(&self) -> Vec<&'static str> {
+        self.passes.iter().map(GraphPass::label).collect()
+    }
+
+    // Orders the passes and creates their pipelines, done automatically by run()
+    #[track_caller]
+    pub fn build(&mut self, device: &Device) -> Result<(), FrameworkError> {
+        if !self.kernels.is_empty() || self.passes.is_empty() {
+            return Ok(());
+        }
+        self.sort_passes()?;
+
+        let mut kernels: Vec<ComputeKernel> = Vec::with_capacity(self.passes.len());
+        for pass in &self.passes {
+            // Bind groups are created per run, for each ping-pong parity
+            let kernel: ComputeKernel = ComputeKernel::builder(&pass.source, &pass.entry_point)
+                .with_label(pass.label)
+                .build(device)?;
+            kernels.push(kernel);
+        }
+        self.kernels = kernels;
+        log::debug!("Compute graph order: {}", self.order().join(" -> "));
+        Ok(())
+    }
+
+    // Stable topological sort over the plain buffers each pass writes and others read
+    fn sort_passes(&mut self) -> Result<(), FrameworkError> {
+        let pass_count: usize = self.passes.len();
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); pass_count];
+        let mut dependency_counts: Vec<usize> = vec![0; pass_count];
+        let mut add_edge = |from: usize, to: usize| {
+            dependents[from].push(to);
+            dependency_counts[to] += 1;
+        };
+
+        for (label, buffer) in &self.buffers {
+            let writers: Vec<usize> = (0..pass_count)
+                .filter(|&index| self.passes[index].writes(label))
+                .collect();
+            // Writers of the same buffer keep the order they were added in
+            for pair in writers.windows(2) {
+                add_edge(pair[0], pair[1]);
+            }
+            // Ping-pong reads refer to the previous write, wherever it happened
+            if let GraphBuffer::PingPong { .. } = buffer {
+                continue;
+            }
+            for reader in (0..pass_count)
+                .filter(|&index| self.passes[index].reads(label) && !writers.contains(&index))
+            {
+                for &writer in &writers {
+                    add_edge(writer, reader);
+                }
+            }
+        }
+
+        // Kahn's algorithm, picking the earliest added pass whenever several are ready
+        let mut ready: BTreeSet<usize> = (0..pass_count)
+            .filter(|&index| dependency_counts[index] == 0)
+            .collect();
+        let mut order: Vec<usize> = Vec::with_capacity(pass_count);
+        while let Some(index) = ready.pop_first() {
+            order.push(index);
+            for &dependent in &dependents[index] {
+                dependency_counts[dependent] -= 1;
+                if dependency_counts[dependent] == 0 {
+                    ready.insert(dependent);
+                }
+            }
+        }
+        if order.len() < pass_count {
+            return Err(FrameworkError::GraphCycle(
+                (0..pass_count)
+                    .filter(|index| !order.contains(index))
+                    .map(|index| self.passes[index].label.to_owned())
+                    .collect(),
+            ));
+        }
+
+        let mut passes: Vec<Option<GraphPass>> = self.passes.drain(..).map(Some).collect();
+        self.passes = order
+            .into_iter()
+            .filter_map(|index| passes[index].take())
+            .collect();
+        Ok(())
+    }
+
+    // <---- Execution ---->
+    // Records `iterations` iterations of every pass into one encoder and submits it
+    #[track_caller]
+    pub fn run(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        iterations: u32,
+    ) -> Result<(), FrameworkError> {
+        // Create command encoder
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("compute_graph_encoder"),
+            });
+        let recorded: RecordedFlips = self.record(device, &mut encoder, iterations)?;
+
+        // Submit commands
+        queue.submit(Some(encoder.finish()));
+        self.apply_flips(recorded);
+        Ok(())
+    }
+
+    // Records `iterations` iterations of every pass into an existing encoder, e.g. ahead of
+    // a render pass reading the buffers
+    // -> Ping-pong pairs are only swapped by apply_flips, after the encoder is submitted,
+    //    until then recorded_buffer gives the buffers later commands should read
+    #[track_caller]
+    pub fn record(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        iterations: u32,
+    ) -> Result<RecordedFlips, FrameworkError> {
+        self.build(device)?;
+
+        // Create bind groups for every buffer parity the run goes through,
+        // which repeats every two iterations
+        let mut flips: FlipMap = self.flips();
+        for _ in 0..iterations.min(2) {
+            for (index, (pass, kernel)) in self.passes.iter().zip(&self.kernels).enumerate() {
+                let (key, buffers) = resolve_bindings(&self.buffers, pass, &flips)?;
+                let ids: Vec<Id<Buffer>> = buffers
+                    .iter()
+                    .map(|(_, buffer)| buffer.global_id())
+                    .collect();
+                let stale: bool = self
+                    .bind_groups
+                    .get(&(index, key))
+                    .is_none_or(|(bound, _)| *bound != ids);
+                if stale {
+                    let bind_group: BindGroup = kernel.create_bind_group(device, &buffers)?;
+                    self.bind_groups.insert((index, key), (ids, bind_group));
+                }
+                advance_flips(&self.buffers, pass, &mut flips);
+            }
+        }
+
+        // Record passes
+        let mut flips: FlipMap = self.flips();
+        for iteration in 0..iterations {
+            encoder.push_debug_group(&format!("iteration {iteration}"));
+            for (index, (pass, kernel)) in self.passes.iter().zip(&self.kernels).enumerate() {
+                let (key, _buffers) = resolve_bindings(&self.buffers, pass, &flips)?;
+                let (_ids, bind_group) = &self.bind_groups[&(index, key)];
+                let mut compute_pass: ComputePass =
+                    encoder.begin_compute_pass(&ComputePassDescriptor {
+                        label: Some(pass.label),
+                        timestamp_writes: None,
+                    });
+                match pass.dispatch {
+                    PassDispatch::Elements(n_elements) => kernel.dispatch_with(
+                        &mut compute_pass,
+                        bind_group,
+                        kernel.workgroups_for(n_elements),
+                    ),
+                    PassDispatch::Grid(width, height) => kernel.dispatch_with(
+                        &mut compute_pass,
+                        bind_group,
+                        kernel.workgroups_for_grid(width, height),
+                    ),
+                    PassDispatch::Workgroups(x, y, z) => {
+                        kernel.dispatch_with(&mut compute_pass, bind_group, (x, y, z))
+                    }
+                    PassDispatch::Indirect { buffer, index } => {
+                        let offset: u64 =
+                            (index as usize * size_of::<DispatchIndirectArgs>()) as u64;
+                        kernel.dispatch_indirect_with(
+                            &mut compute_pass,
+                            bind_group,
+                            front_buffer(&self.buffers, buffer, &flips)?,
+                            offset,
+                        )
+                    }
+                }
+                drop(compute_pass);
+                advance_flips(&self.buffers, pass, &mut flips);
+            }
+            encoder.pop_debug_group();
+        }
+        Ok(RecordedFlips { flips })
+    }
+
+    pub fn apply_flips(&mut self, recorded: RecordedFlips) {
+        for (label, buffer) in self.buffers.iter_mut() {
+            if let (GraphBuffer::PingPong { flips: current, .. }, Some(flips)) =
+                (buffer, recorded.flips.get(label))
+            {
+                *current = *flips;
+            }
+        }
+    }
+
+    // Latest contents once the recorded passes have run
+    pub fn recorded_buffer(
+        &self,
+        label: &str,
+        recorded: &RecordedFlips,
+    ) -> Result<&Buffer, FrameworkError> {
+        front_buffer(&self.buffers, label, &recorded.flips)
+    }
+
+    fn flips(&self) -> FlipMap {
+        self.buffers
+            .iter()
+            .filter_map(|(label, buffer)| match buffer {
+                GraphBuffer::PingPong { flips, .. } => Some((*label, *flips)),
+                GraphBuffer::Single(_) => None,
+            })
+            .collect()
+    }
+}
+
+fn resolve_bindings<'a>(
+    buffers: &'a FxHashMap<&'static str, GraphBuffer>,
+    pass: &GraphPass,
+    flips: &FlipMap,
+) -> Result<ResolvedBindings<'a>, FrameworkError> {
+    let mut key: u64 = 0;
+    let mut bindings: Vec<(u32, &Buffer)> = Vec::with_capacity(pass.bindings.len());
+    for (position, (binding, label, access)) in pass.bindings.iter().enumerate() {
+        let buffer: &Buffer = match buffers.get(label) {
+            Some(GraphBuffer::Single(buffer)) => buffer,
+            Some(GraphBuffer::PingPong { buffers, .. }) => {
+                let front: u64 = flips[label] % 2;
+                let index: u64 = match access {
+                    Access::Read => front,
+                    Access::Write => 1 - front,
+                };
+                key |= index << position;
+                &buffers[index as usize]
+            }
+            None => return Err(FrameworkError::UnknownBuffer(label.to_string())),
+        };
+        bindings.push((*binding, buffer));
+    }
+    Ok((key, bindings))
+}
+
+// Latest contents of a buffer, as of the given flips
+fn front_buffer<'a>(
+    buffers: &'a FxHashMap<&'static str, GraphBuffer>,
+    label: &str,
+    flips: &FlipMap,
+) -> Result<&'a Buffer, FrameworkError> {
+    match buffers.get(label) {
+        Some(GraphBuffer::Single(buffer)) => Ok(buffer),
+        Some(GraphBuffer::PingPong { buffers, .. }) => Ok(&buffers[(flips[label] % 2) as usize]),
+        None => Err(FrameworkError::UnknownBuffer(label.to_owned())),
+    }
+}
+
+// Swaps the ping-pong pairs written by a pass
+fn advance_flips(
+    buffers: &FxHashMap<&'static str, GraphBuffer>,
+    pass: &GraphPass,
+    flips: &mut FlipMap,
+) {
+    for (label, buffer) in buffers {
+        if matches!(buffer, GraphBuffer::PingPong { .. }) && pass.writes(label) {
+            *flips.get_mut(label).unwrap() += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor, Id};
+
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_context::GPUContext, utilities::create_buffer},
+        verification::verification_context,
+    };
+
+    use super::{ComputeGraph, GraphPass, PassDispatch, RecordedFlips};
+
+    const INCREMENT_WGSL: &str = "
+@group(0) @binding(0) var<storage, read> state_in: array<u32>;
+@group(0) @binding(1) var<storage, read_write> state_out: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < arrayLength(&state_in) {
+        state_out[id.x] = state_in[id.x] + 1u;
+    }
+}
+";
+
+    fn increment_graph(context: &GPUContext) -> Result<ComputeGraph, FrameworkError> {
+        let usage: BufferUsages = BufferUsages::STORAGE | BufferUsages::COPY_SRC;
+        let initial: [u32; 4] = [0, 10, 20, 30];
+        let mut graph: ComputeGraph = ComputeGraph::new();
+        graph.add_ping_pong(
+            "state",
+            create_buffer(&context.device, bytemuck::cast_slice(&initial), usage, None)?,
+            create_buffer(&context.device, &[0; 16], usage, None)?,
+        );
+        graph.add_pass(
+            GraphPass::new("increment", INCREMENT_WGSL, "main")
+                .with_read(0, "state")
+                .with_write(1, "state")
+                .with_dispatch(PassDispatch::Elements(4)),
+        );
+        Ok(graph)
+    }
+
+    #[test]
+    fn unsubmitted_records_leave_buffers_unchanged() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let mut graph: ComputeGraph = increment_graph(&context)?;
+        let front: Id<Buffer> = graph.buffer("state")?.global_id();
+
+        // Dropped without being submitted
+        let mut encoder: CommandEncoder = context
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor { label: None });
+        let recorded: RecordedFlips = graph.record(&context.device, &mut encoder, 3)?;
+        let back: Id<Buffer> = graph.recorded_buffer("state", &recorded)?.global_id();
+        assert_ne!(front, back);
+        drop(encoder);
+        drop(recorded);
+        assert_eq!(graph.buffer("state")?.global_id(), front);
+
+        graph.run(&context.device, &context.queue, 3)?;
+        assert_eq!(graph.buffer("state")?.global_id(), back);
+        let state: Vec<u32> = graph.read(&context.device, &context.queue, "state")?;
+        assert_eq!(state, [3, 13, 23, 33]);
+        Ok(())
+    }
+
+    // Elementwise steps between buffers of 4 u32
+    const STEPS_WGSL: &str = "
+@group(0) @binding(0) var<storage, read> input: array<u32>;
+@group(0) @binding(1) var<storage, read_write> output: array<u32>;
+@group(0) @binding(2) var<storage, read> addend: array<u32>;
+
+@compute @workgroup_size(4)
+fn fill(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = id.x + 1u;
+}
+
+@compute @workgroup_size(4)
+fn times_ten(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x] * 10u;
+}
+
+@compute @workgroup_size(4)
+fn copy(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x];
+}
+
+@compute @workgroup_size(4)
+fn add(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = input[id.x] + addend[id.x];
+}
+";
+
+    fn zeroed_buffer(context: &GPUContext) -> Result<Buffer, FrameworkError> {
+        create_buffer(
+            &context.device,
+            &[0; 16],
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            None,
+        )
+    }
+
+    #[test]
+    fn passes_run_after_their_dependencies() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let mut graph: ComputeGraph = ComputeGraph::new();
+        for label in ["filled", "scaled", "copied"] {
+            graph.add_buffer(label, zeroed_buffer(&context)?);
+        }
+        // Consumers first
+        graph.add_pass(
+            GraphPass::new("copy", STEPS_WGSL, "copy")
+                .with_read(0, "scaled")
+                .with_write(1, "copied"),
+        );
+        graph.add_pass(
+            GraphPass::new("times_ten", STEPS_WGSL, "times_ten")
+                .with_read(0, "filled")
+                .with_write(1, "scaled"),
+        );
+        graph.add_pass(GraphPass::new("fill", STEPS_WGSL, "fill").with_write(1, "filled"));
+
+        graph.run(&context.device, &context.queue, 1)?;
+        assert_eq!(graph.order(), ["fill", "times_ten", "copy"]);
+        let copied: Vec<u32> = graph.read(&context.device, &context.queue, "copied")?;
+        assert_eq!(copied, [10, 20, 30, 40]);
+        Ok(())
+    }
+
+    #[test]
+    fn cycles_are_rejected() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let mut graph: ComputeGraph = ComputeGraph::new();
+        for label in ["first", "second", "third"] {
+            graph.add_buffer(label, zeroed_buffer(&context)?);
+        }
+        graph.add_pass(GraphPass::new("fill", STEPS_WGSL, "fill").with_write(1, "third"));
+        graph.add_pass(
+            GraphPass::new("forward", STEPS_WGSL, "copy")
+                .with_read(0, "first")
+                .with_write(1, "second"),
+        );
+        graph.add_pass(
+            GraphPass::new("backward", STEPS_WGSL, "copy")
+                .with_read(0, "second")
+                .with_write(1, "first"),
+        );
+
+        let result: Result<(), FrameworkError> = graph.run(&context.device, &context.queue, 1);
+        let Err(FrameworkError::GraphCycle(mut passes)) = result else {
+            panic!("Expected a cycle error, got {result:?}");
+        };
+        passes.sort();
+        assert_eq!(passes, ["backward", "forward"]);
+        Ok(())
+    }
+
+    #[test]
+    fn ping_pong_iterations() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        for iterations in [1, 2, 3, 4] {
+            // Each iteration: state += 1, then total += state, then snapshot = total
+            let mut graph: ComputeGraph = increment_graph(&context)?;
+            graph.add_ping_pong("total", zeroed_buffer(&context)?, zeroed_buffer(&context)?);
+            graph.add_buffer("snapshot", zeroed_buffer(&context)?);
+            graph.add_pass(
+                GraphPass::new("accumulate", STEPS_WGSL, "add")
+                    .with_read(0, "total")
+                    .with_write(1, "total")
+                    .with_read(2, "state")
+                    .with_dispatch(PassDispatch::Elements(4)),
+            );
+            graph.add_pass(
+                GraphPass::new("snapshot", STEPS_WGSL, "copy")
+                    .with_read(0, "total")
+                    .with_write(1, "snapshot"),
+            );
+
+            let (mut state, mut total): ([u32; 4], [u32; 4]) = ([0, 10, 20, 30], [0; 4]);
+            // Split in two runs, which must continue from each other's buffers
+            for run in [iterations / 2, iterations - iterations / 2] {
+                graph.run(&context.device, &context.queue, run)?;
+                for _ in 0..run {
+                    state = state.map(|value| value + 1);
+                    total = [0, 1, 2, 3].map(|index| total[index] + state[index]);
+                }
+            }
+            let (device, queue) = (&context.device, &context.queue);
+            assert_eq!(graph.read::<u32>(device, queue, "state")?, state);
+            assert_eq!(graph.read::<u32>(device, queue, "total")?, total);
+            assert_eq!(graph.read::<u32>(device, queue, "snapshot")?, total);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+use wgpu::{
+    BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, Buffer, CommandEncoder,
+    CommandEncoderDescriptor, ComputePass, ComputePassDescriptor, ComputePipeline, Device, Id,
+    PipelineLayout, Queue, ShaderModule,
+};
+
+use crate::framework::error::FrameworkError;
+
+use super::{
+    indirect::{DispatchIndirectArgs, IndirectBuffer},
+    utilities::{
+        create_bind_group_entry, create_bind_group_layout, create_bind_group_with_entries,
+        create_compute_pipeline, create_compute_pipeline_with_derived_layout,
+        create_pipeline_layout, create_wgsl_shader_module,
+    },
+};
+
+// Compute pipeline bound to its buffers, created once and dispatched any number of times
+// -> Buffers are bound in bind group 0, every binding used by the entry point must be given
+// -> Bind groups reference the buffers themselves, call bind() with the current buffers
+//    before dispatching whenever one may have been re-created (e.g. when a GpuBuffer grows
+//    and its generation changes), the bind group is only recreated when one was
+// -> Kernels built without buffers are dispatched with bind groups from create_bind_group()
+pub struct ComputeKernel {
+    label: Option<String>,
+    pipeline: ComputePipeline,
+    bind_group_layout: BindGroupLayout,
+    bind_group: Option<BindGroup>,
+    // Ids of the buffers in bind_group, which change whenever a buffer is re-created
+    bound_buffers: Vec<(u32, Id<Buffer>)>,
+    workgroup_size: [u32; 3],
+    max_workgroups_per_dimension: u32,
+}
+
+pub struct ComputeKernelBuilder<'a> {
+    source: &'a str,
+    entry_point: &'a str,
+    label: Option<&'a str>,
+    buffers: Vec<(u32, &'a Buffer)>,
+    layout_entries: Option<&'a [BindGroupLayoutEntry]>,
+}
+
+impl<'a> ComputeKernelBuilder<'a> {
+    pub fn new(source: &'a str, entry_point: &'a str) -> Self {
+        Self {
+            source,
+            entry_point,
+            label: None,
+            buffers: Vec::new(),
+            layout_entries: None,
+        }
+    }
+
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_buffer(mut self, binding: u32, buffer: &'a Buffer) -> Self {
+        self.buffers.push((binding, buffer));
+        self
+    }
+
+    // Explicit layout of bind group 0, for resources the derived layout doesn't accept
+    // (e.g. unfilterable float textures, as derived layouts expect filterable ones)
+    pub fn with_layout_entries(mut self, entries: &'a [BindGroupLayoutEntry]) -> Self {
+        self.layout_entries = Some(entries);
+        self
+    }
+
+    #[track_caller]
+    pub fn build(self, device: &Device) -> Result<ComputeKernel, FrameworkError> {
+        let workgroup_size: [u32; 3] = reflect_workgroup_size(self.source, self.entry_point)
+            .map_err(|message| FrameworkError::ShaderReflection {
+                label: self.label.unwrap_or("<unlabelled>").to_owned(),
+                message,
+            })?;
+
+        // Create compute pipeline, unless given its layout is derived from the shader so that
+        // buffer access modes (read, read_write, uniform) always match
+        let shader: ShaderModule = create_wgsl_shader_module(device, self.source, self.label)?;
+        let (pipeline, bind_group_layout): (ComputePipeline, BindGroupLayout) =
+            match self.layout_entries {
+                Some(entries) => {
+                    let bind_group_layout: BindGroupLayout =
+                        create_bind_group_layout(device, entries, self.label)?;
+                    let pipeline_layout: PipelineLayout =
+                        create_pipeline_layout(device, &[&bind_group_layout], self.label)?;
+                    let pipeline: ComputePipeline = create_compute_pipeline(
+                        device,
+                        &pipeline_layout,
+                        &shader,
+                        self.entry_point,
+                        self.label,
+                    )?;
+                    (pipeline, bind_group_layout)
+                }
+                None => {
+                    let pipeline: ComputePipeline = create_compute_pipeline_with_derived_layout(
+                        device,
+                        &shader,
+                        self.entry_point,
+                        self.label,
+                    )?;
+                    let bind_group_layout: BindGroupLayout = pipeline.get_bind_group_layout(0);
+                    (pipeline, bind_group_layout)
+                }
+            };
+        let bind_group: Option<BindGroup> = match self.buffers.is_empty() {
+            true => None,
+            false => Some(create_kernel_bind_group(
+                device,
+                &bind_group_layout,
+                &self.buffers,
+                self.label,
+            )?),
+        };
+
+        Ok(ComputeKernel {
+            label: self.label.map(str::to_owned),
+            pipeline,
+            bind_group_layout,
+            bind_group,
+            bound_buffers: buffer_ids(&self.buffers),
+            workgroup_size,
+            max_workgroups_per_dimension: device.limits().max_compute_workgroups_per_dimension,
+        })
+    }
+}
+
+impl ComputeKernel {
+    pub fn builder<'a>(source: &'a str, entry_point: &'a str) -> ComputeKernelBuilder<'a> {
+        ComputeKernelBuilder::new(source, entry_point)
+    }
+
+    // <---- Accessors ---->
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    // As declared by the entry point's @workgroup_size
+    pub fn workgroup_size(&self) -> [u32; 3] {
+        self.workgroup_size
+    }
+
+    pub fn invocations_per_workgroup(&self) -> u32 {
+        self.workgroup_size.iter().product()
+    }
+
+    pub fn pipeline(&self) -> &ComputePipeline {
+        &self.pipeline
+    }
+
+    pub fn bind_group_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> Option<&BindGroup> {
+        self.bind_group.as_ref()
+    }
+
+    // Replaces the bound buffers
+    #[track_caller]
+    pub fn rebind(
+        &mut self,
+        device: &Device,
+        buffers: &[(u32, &Buffer)],
+    ) -> Result<(), FrameworkError> {
+        self.bind_group = Some(self.create_bind_group(device, buffers)?);
+        self.bound_buffers = buffer_ids(buffers);
+        Ok(())
+    }
+
+    // Binds the buffers unless they are the ones already bound, returns whether the bind
+    // group was recreated
+    #[track_caller]
+    pub fn bind(
+        &mut self,
+        device: &Device,
+        buffers: &[(u32, &Buffer)],
+    ) -> Result<bool, FrameworkError> {
+        if self.bind_group.is_some() && self.bound_buffers == buffer_ids(buffers) {
+            return Ok(false);
+        }
+        self.rebind(device, buffers)?;
+        Ok(true)
+    }
+
+    // Additional bind group for the kernel's layout, e.g. to alternate between buffer sets
+    #[track_caller]
+    pub fn create_bind_group(
+        &self,
+        device: &Device,
+        buffers: &[(u32, &Buffer)],
+    ) -> Result<BindGroup, FrameworkError> {
+        create_kernel_bind_group(device, &self.bind_group_layout, buffers, self.label())
+    }
+
+    // <---- Dispatch sizing ---->
+    // Workgroup counts covering at least `n_elements` invocations
+    // -> Counts above max_compute_workgroups_per_dimension are split across y and z, so the
+    //    shader must linearise its index and discard invocations past the element count:
+    //    index = id.x + (id.y + id.z * num_workgroups.y) * num_workgroups.x * workgroup_size.x
+    //    (for a workgroup size of (workgroup_size.x, 1, 1))
+    pub fn workgroups_for(&self, n_elements: u32) -> (u32, u32, u32) {
+        split_workgroups(
+            n_elements.div_ceil(self.invocations_per_workgroup()),
+            self.max_workgroups_per_dimension,
+        )
+    }
+
+    // Workgroup counts covering a width x height grid, one invocation per cell
+    pub fn workgroups_for_grid(&self, width: u32, height: u32) -> (u32, u32, u32) {
+        (
+            width.div_ceil(self.workgroup_size[0]),
+            height.div_ceil(self.workgroup_size[1]),
+            1,
+        )
+    }
+
+    // <---- Dispatch ---->
+    // Panics if the kernel was built without buffers
+    pub fn dispatch<'a>(&'a self, compute_pass: &mut ComputePass<'a>, workgroups: (u32, u32, u32)) {
+        let bind_group: &BindGroup = self
+            .bind_group
+            .as_ref()
+            .expect("Kernel built without buffers must be dispatched with a bind group");
+        self.dispatch_with(compute_pass, bind_group, workgroups);
+    }
+
+    pub fn dispatch_with<'a>(
+        &'a self,
+        compute_pass: &mut ComputePass<'a>,
+        bind_group: &'a BindGroup,
+        workgroups: (u32, u32, u32),
+    ) {
+        let (x, y, z) = workgroups;
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups(x, y, z);
+    }
+
+    // Workgroup counts are read from the arguments at `index`, e.g. written by a previous pass
+    // -> Panics if the kernel was built without buffers
+    pub fn dispatch_indirect<'a>(
+        &'a self,
+        compute_pass: &mut ComputePass<'a>,
+        args: &'a IndirectBuffer<DispatchIndirectArgs>,
+        index: usize,
+    ) {
+        let bind_group: &BindGroup = self
+            .bind_group
+            .as_ref()
+            .expect("Kernel built without buffers must be dispatched with a bind group");
+        self.dispatch_indirect_with(compute_pass, bind_group, args.buffer(), args.offset(index));
+    }
+
+    // `buffer` must have the INDIRECT usage and hold DispatchIndirectArgs at `offset`
+    pub fn dispatch_indirect_with<'a>(
+        &'a self,
+        compute_pass: &mut ComputePass<'a>,
+        bind_group: &'a BindGroup,
+        buffer: &'a Buffer,
+        offset: u64,
+    ) {
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, bind_group, &[]);
+        compute_pass.dispatch_workgroups_indirect(buffer, offset);
+    }
+
+    pub fn dispatch_for<'a>(&'a self, compute_pass: &mut ComputePass<'a>, n_elements: u32) {
+        self.dispatch(compute_pass, self.workgroups_for(n_elements));
+    }
+
+    // Records the dispatch into its own compute pass and submits it
+    pub fn run_for(&self, device: &Device, queue: &Queue, n_elements: u32) {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("compute_kernel_encoder"),
+            });
+        {
+            let mut compute_pass: ComputePass =
+                encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: self.label(),
+                    timestamp_writes: None,
+                });
+            self.dispatch_for(&mut compute_pass, n_elements);
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+// Reads the workgroup size of a WGSL entry point
+fn reflect_workgroup_size(source: &str, entry_point: &str) -> Result<[u32; 3], String> {
+    let module: naga::Module =
+        naga::front::wgsl::parse_str(source).map_err(|err| err.emit_to_string(source))?;
+    module
+        .entry_points
+        .iter()
+        .find(|entry| entry.name == entry_point && entry.stage == naga::ShaderStage::Compute)
+        .map(|entry| entry.workgroup_size)
+        .ok_or_else(|| format!("No compute entry point named '{entry_point}'"))
+}
+
+// Spreads a workgroup count over up to three dimensions of at most `max` workgroups each,
+// keeping the number of surplus workgroups small
+fn split_workgroups(workgroups: u32, max: u32) -> (u32, u32, u32) {
+    if workgroups <= max {
+        return (workgroups, 1, 1);
+    }
+    let z: u32 = workgroups.div_ceil(max.saturating_mul(max));
+    let per_layer: u32 = workgroups.div_ceil(z);
+    let y: u32 = per_layer.div_ceil(max);
+    let x: u32 = per_layer.div_ceil(y);
+    (x, y, z)
+}
+
+fn buffer_ids(buffers: &[(u32, &Buffer)]) -> Vec<(u32, Id<Buffer>)> {
+    buffers
+        .iter()
+        .map(|(binding, buffer)| (*binding, buffer.global_id()))
+        .collect()
+}
+
+#[track_caller]
+fn create_kernel_bind_group(
+    device: &Device,
+    layout: &BindGroupLayout,
+    buffers: &[(u32, &Buffer)],
+    label: Option<&str>,
+) -> Result<BindGroup, FrameworkError> {
+    let entries: Vec<BindGroupEntry> = buffers
+        .iter()
+        .map(|(binding, buffer)| create_bind_group_entry(*binding, buffer))
+        .collect();
+    create_bind_group_with_entries(device, &entries, layout, label)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{
+            device_requirements::DeviceRequirements, gpu_buffer::GpuBuffer, gpu_context::GPUContext,
+        },
+        verification::{verification_context, verification_context_with_requirements},
+    };
+
+    use super::{split_workgroups, ComputeKernel};
+
+    // Covers the count without exceeding the limit in any dimension
+    #[track_caller]
+    fn assert_split(workgroups: u32, max: u32) -> (u32, u32, u32) {
+        let (x, y, z) = split_workgroups(workgroups, max);
+        assert!(
+            x <= max && y <= max && z <= max,
+            "{workgroups} over ({x}, {y}, {z}), limit {max}"
+        );
+        assert!(
+            x as u64 * y as u64 * z as u64 >= workgroups as u64,
+            "{workgroups} over ({x}, {y}, {z})"
+        );
+        (x, y, z)
+    }
+
+    #[test]
+    fn split_workgroups_within_limits() {
+        assert_eq!(assert_split(0, 4), (0, 1, 1));
+        // Exact limit, limit + 1, non-multiples and counts needing z
+        assert_eq!(assert_split(4, 4), (4, 1, 1));
+        assert_eq!(assert_split(5, 4), (3, 2, 1));
+        assert_eq!(assert_split(11, 4), (4, 3, 1));
+        assert_eq!(assert_split(16, 4), (4, 4, 1));
+        assert_eq!(assert_split(17, 4), (3, 3, 2));
+        assert_eq!(assert_split(64, 4), (4, 4, 4));
+        for workgroups in 0..=64 {
+            assert_split(workgroups, 4);
+        }
+
+        let max: u32 = 65_535;
+        assert_eq!(assert_split(max, max), (max, 1, 1));
+        assert_eq!(assert_split(max + 1, max), (32_768, 2, 1));
+        assert_split(max * 3 + 7, max);
+        assert_eq!(assert_split(max * max, max), (max, max, 1));
+        assert_eq!(assert_split(max * max + 1, max).2, 2);
+        assert_split(u32::MAX, max);
+    }
+
+    #[test]
+    fn workgroups_for_elements() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context_with_requirements(
+            &DeviceRequirements::new().with_required_limits(wgpu::Limits {
+                max_compute_workgroups_per_dimension: 4,
+                ..Default::default()
+            }),
+        )?;
+        let kernel: ComputeKernel =
+            ComputeKernel::builder(DOUBLE_WGSL, "main").build(&context.device)?;
+        assert_eq!(kernel.workgroups_for(0), (0, 1, 1));
+        assert_eq!(kernel.workgroups_for(1), (1, 1, 1));
+        assert_eq!(kernel.workgroups_for(256), (4, 1, 1));
+        assert_eq!(kernel.workgroups_for(257), (3, 2, 1));
+        assert_eq!(kernel.workgroups_for(64 * 17), (3, 3, 2));
+        assert_eq!(kernel.workgroups_for_grid(65, 3), (2, 3, 1));
+        Ok(())
+    }
+
+    const DOUBLE_WGSL: &str = "
+@group(0) @binding(0) var<storage, read_write> values: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < arrayLength(&values) {
+        values[id.x] *= 2u;
+    }
+}
+";
+
+    #[test]
+    fn grown_buffers_are_rebound() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let (device, queue) = (&context.device, &context.queue);
+        let mut values: GpuBuffer<u32> = GpuBuffer::storage(device, &[1, 2], Some("values"))?;
+        let mut kernel: ComputeKernel = ComputeKernel::builder(DOUBLE_WGSL, "main")
+            .with_buffer(0, values.buffer())
+            .build(device)?;
+        assert!(!kernel.bind(device, &[(0, values.buffer())])?);
+
+        let contents: Vec<u32> = (0..100).collect();
+        values.write(device, queue, &contents)?;
+        assert!(kernel.bind(device, &[(0, values.buffer())])?);
+        assert!(!kernel.bind(device, &[(0, values.buffer())])?);
+        kernel.run_for(device, queue, values.len() as u32);
+        let doubled: Vec<u32> = contents.iter().map(|value| value * 2).collect();
+        assert_eq!(values.read(device, queue)?, doubled);
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use wgpu::{Adapter, Features, Limits};
+
+// Features and limits requested when creating the logical device
+// -> Required features and limits must be supported by the adapter
+// -> Optional features are only enabled when the adapter supports them
+#[derive(Clone, Debug)]
+pub struct DeviceRequirements {
+    pub required_features: Features,
+    pub optional_features: Features,
+    pub required_limits: Limits,
+}
+
+impl Default for DeviceRequirements {
+    fn default() -> Self {
+        Self {
+            required_features: Features::empty(),
+            optional_features: Features::empty(),
+            required_limits: Limits::default(),
+        }
+    }
+}
+
+impl DeviceRequirements {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // <---- Builder ---->
+    pub fn with_required_features(mut self, features: Features) -> Self {
+        self.required_features |= features;
+        self
+    }
+
+    pub fn with_optional_features(mut self, features: Features) -> Self {
+        self.optional_features |= features;
+        self
+    }
+
+    pub fn with_required_limits(mut self, limits: Limits) -> Self {
+        self.required_limits = limits;
+        self
+    }
+
+    // <---- Negotiation ---->
+    pub fn missing_features(&self, adapter: &Adapter) -> Features {
+        self.required_features - adapter.features()
+    }
+
+    // (limit name, requested, allowed) for every limit the adapter cannot satisfy
+    pub fn unsupported_limits(&self, adapter: &Adapter) -> Vec<(&'static str, u64, u64)> {
+        let mut unsupported: Vec<(&'static str, u64, u64)> = Vec::new();
+        self.required_limits.check_limits_with_fail_fn(
+            &adapter.limits(),
+            false,
+            |name, requested, allowed| unsupported.push((name, requested, allowed)),
+        );
+        unsupported
+    }
+
+    pub fn granted_features(&self, adapter: &Adapter) -> Features {
+        self.required_features | (self.optional_features & adapter.features())
+    }
+}
@@ -0,0 +1,362 @@
+use std::{marker::PhantomData, mem::size_of};
+
+use bytemuck::Pod;
+use wgpu::{
+    BindingResource, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor, Device, Queue,
+};
+
+use crate::framework::error::FrameworkError;
+
+use super::{
+    readback::{Readback, ReadbackFuture},
+    utilities::{create_buffer, create_empty_buffer},
+};
+
+// Buffer of `len` elements of type T
+// -> T must be a multiple of 4 bytes, as required by buffer copies (and WGSL)
+// -> COPY_SRC and COPY_DST are always added so the buffer can be written, read and grown
+// -> Growing re-creates the underlying buffer, bind groups using it must be
+//    recreated whenever the generation changes
+pub struct GpuBuffer<T: Pod> {
+    buffer: Buffer,
+    len: usize,
+    capacity: usize,
+    usage: BufferUsages,
+    label: Option<String>,
+    generation: u64,
+    _element: PhantomData<T>,
+}
+
+impl<T: Pod> GpuBuffer<T> {
+    const ELEMENT_SIZE_CHECK: () = assert!(
+        size_of::<T>().is_multiple_of(4),
+        "GpuBuffer elements must be a multiple of 4 bytes"
+    );
+
+    #[track_caller]
+    pub fn new(
+        device: &Device,
+        contents: &[T],
+        usage: BufferUsages,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        let () = Self::ELEMENT_SIZE_CHECK;
+        let usage: BufferUsages = usage | BufferUsages::COPY_SRC | BufferUsages::COPY_DST;
+        // Buffers can't be empty
+        let buffer: Buffer = match contents.is_empty() {
+            true => create_empty_buffer(device, Self::byte_size(0), usage, label)?,
+            false => create_buffer(device, bytemuck::cast_slice(contents), usage, label)?,
+        };
+        Ok(Self {
+            buffer,
+            len: contents.len(),
+            capacity: contents.len(),
+            usage,
+            label: label.map(str::to_owned),
+            generation: 0,
+            _element: PhantomData,
+        })
+    }
+
+    // Buffer of `len` zeroed elements
+    #[track_caller]
+    pub fn zeroed(
+        device: &Device,
+        len: usize,
+        usage: BufferUsages,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        let () = Self::ELEMENT_SIZE_CHECK;
+        let usage: BufferUsages = usage | BufferUsages::COPY_SRC | BufferUsages::COPY_DST;
+        Ok(Self {
+            buffer: create_empty_buffer(device, Self::byte_size(len), usage, label)?,
+            len,
+            capacity: len,
+            usage,
+            label: label.map(str::to_owned),
+            generation: 0,
+            _element: PhantomData,
+        })
+    }
+
+    #[track_caller]
+    pub fn storage(
+        device: &Device,
+        contents: &[T],
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        Self::new(device, contents, BufferUsages::STORAGE, label)
+    }
+
+    #[track_caller]
+    pub fn uniform(
+        device: &Device,
+        contents: &T,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        Self::new(
+            device,
+            std::slice::from_ref(contents),
+            BufferUsages::UNIFORM,
+            label,
+        )
+    }
+
+    // <---- Accessors ---->
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn usage(&self) -> BufferUsages {
+        self.usage
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    // Incremented every time the underlying buffer is re-created
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn as_entire_binding(&self) -> BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+
+    // <---- Upload ---->
+    // Replaces the contents, growing the buffer if needed
+    #[track_caller]
+    pub fn write(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        contents: &[T],
+    ) -> Result<(), FrameworkError> {
+        if contents.len() > self.capacity {
+            self.reallocate(device, queue, contents.len(), false)?;
+        }
+        self.len = contents.len();
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(contents));
+        Ok(())
+    }
+
+    // Overwrites elements starting at `offset` without changing the length
+    pub fn write_range(
+        &self,
+        queue: &Queue,
+        offset: usize,
+        contents: &[T],
+    ) -> Result<(), FrameworkError> {
+        // Overflowing ends are out of range too
+        let in_range: bool = offset
+            .checked_add(contents.len())
+            .is_some_and(|end| end <= self.len);
+        if !in_range {
+            return Err(FrameworkError::InvalidBufferRange {
+                label: self.label().unwrap_or("<unlabelled>").to_owned(),
+                message: format!(
+                    "write of {} elements at {offset} exceeds length {}",
+                    contents.len(),
+                    self.len
+                ),
+            });
+        }
+        queue.write_buffer(
+            &self.buffer,
+            (offset * size_of::<T>()) as u64,
+            bytemuck::cast_slice(contents),
+        );
+        Ok(())
+    }
+
+    // <---- Download ---->
+    // Blocks until the contents are available
+    #[track_caller]
+    pub fn read(&self, device: &Device, queue: &Queue) -> Result<Vec<T>, FrameworkError> {
+        self.readback(device, queue)?.wait(device)
+    }
+
+    // Returns immediately, the contents can be polled for or awaited
+    #[track_caller]
+    pub fn read_async(
+        &self,
+        device: &Device,
+        queue: &Queue,
+    ) -> Result<ReadbackFuture<T>, FrameworkError> {
+        Ok(self.readback(device, queue)?.into_future())
+    }
+
+    #[track_caller]
+    pub fn readback(&self, device: &Device, queue: &Queue) -> Result<Readback, FrameworkError> {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("gpu_buffer_readback_encoder"),
+            });
+        let readback: Readback = self.record_readback(device, &mut encoder)?;
+        queue.submit(Some(encoder.finish()));
+        readback.map();
+        Ok(readback)
+    }
+
+    // Records a copy of the elements into an existing encoder, map the readback once submitted
+    #[track_caller]
+    pub fn record_readback(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+    ) -> Result<Readback, FrameworkError> {
+        Readback::record_buffer_range(
+            device,
+            encoder,
+            &self.buffer,
+            0,
+            (self.len * size_of::<T>()) as u64,
+        )
+    }
+
+    // <---- Resizing ---->
+    // Changes the length, keeping existing elements and zeroing new ones
+    #[track_caller]
+    pub fn resize(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        len: usize,
+    ) -> Result<(), FrameworkError> {
+        if len > self.capacity {
+            self.reallocate(device, queue, len, true)?;
+        }
+        if len > self.len {
+            // Clear elements left over from a previous, longer length
+            let mut encoder: CommandEncoder =
+                device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("gpu_buffer_clear_encoder"),
+                });
+            encoder.clear_buffer(
+                &self.buffer,
+                (self.len * size_of::<T>()) as u64,
+                Some(((len - self.len) * size_of::<T>()) as u64),
+            );
+            queue.submit(Some(encoder.finish()));
+        }
+        self.len = len;
+        Ok(())
+    }
+
+    #[track_caller]
+    fn reallocate(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        capacity: usize,
+        keep_contents: bool,
+    ) -> Result<(), FrameworkError> {
+        // Grow geometrically to amortise repeated resizes
+        let capacity: usize = capacity.max(self.capacity * 2);
+        let buffer: Buffer =
+            create_empty_buffer(device, Self::byte_size(capacity), self.usage, self.label())?;
+
+        // Copy existing contents
+        if keep_contents && self.len > 0 {
+            let mut encoder: CommandEncoder =
+                device.create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("gpu_buffer_resize_encoder"),
+                });
+            encoder.copy_buffer_to_buffer(
+                &self.buffer,
+                0,
+                &buffer,
+                0,
+                (self.len * size_of::<T>()) as u64,
+            );
+            queue.submit(Some(encoder.finish()));
+        }
+
+        log::debug!(
+            "Reallocated buffer {} from {} to {capacity} elements",
+            self.label().unwrap_or("<unlabelled>"),
+            self.capacity
+        );
+        self.buffer = buffer;
+        self.capacity = capacity;
+        self.generation += 1;
+        Ok(())
+    }
+
+    // Buffers must hold at least one element
+    fn byte_size(len: usize) -> u64 {
+        (len.max(1) * size_of::<T>()) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError, gpu::gpu_context::GPUContext, verification::verification_context,
+    };
+
+    use super::GpuBuffer;
+
+    #[test]
+    fn write_range_checks_bounds() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let buffer: GpuBuffer<u32> =
+            GpuBuffer::storage(&context.device, &[0; 8], Some("write_range"))?;
+
+        buffer.write_range(&context.queue, 6, &[1, 2])?;
+        assert!(buffer.write_range(&context.queue, 7, &[1, 2]).is_err());
+        // offset + len overflows
+        assert!(matches!(
+            buffer.write_range(&context.queue, usize::MAX, &[1]),
+            Err(FrameworkError::InvalidBufferRange { .. })
+        ));
+
+        let contents: Vec<u32> = buffer.read(&context.device, &context.queue)?;
+        assert_eq!(contents, [0, 0, 0, 0, 0, 0, 1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn growing_keeps_contents_and_changes_generation() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let (device, queue) = (&context.device, &context.queue);
+        let mut buffer: GpuBuffer<u32> = GpuBuffer::storage(device, &[1, 2, 3], Some("grow"))?;
+        assert_eq!(buffer.generation(), 0);
+
+        // Within capacity
+        buffer.resize(device, queue, 2)?;
+        buffer.resize(device, queue, 3)?;
+        assert_eq!(buffer.generation(), 0);
+        assert_eq!(buffer.read(device, queue)?, [1, 2, 0]);
+
+        // Past capacity, existing elements are copied and new ones zeroed
+        buffer.resize(device, queue, 5)?;
+        assert_eq!(buffer.generation(), 1);
+        assert!(buffer.capacity() >= 5);
+        assert_eq!(buffer.read(device, queue)?, [1, 2, 0, 0, 0]);
+
+        // Writes replace the contents, growing as needed
+        let contents: Vec<u32> = (0..20).collect();
+        buffer.write(device, queue, &contents)?;
+        assert_eq!(buffer.generation(), 2);
+        assert_eq!(buffer.len(), 20);
+        assert_eq!(buffer.read(device, queue)?, contents);
+        buffer.write(device, queue, &[7, 8])?;
+        assert_eq!(buffer.generation(), 2);
+        assert_eq!(buffer.read(device, queue)?, [7, 8]);
+        Ok(())
+    }
+}
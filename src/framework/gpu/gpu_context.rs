@@ -0,0 +1,123 @@
+use std::{path::PathBuf, sync::Arc};
+
+use wgpu::{Adapter, Device, DeviceDescriptor, Features, Instance, Queue, Surface};
+
+use crate::framework::error::FrameworkError;
+
+use super::{
+    adapter_selection::AdapterSelection, api_trace::ApiTrace,
+    device_requirements::DeviceRequirements, gpu_info::GpuInfo, validation,
+};
+
+// Surface-less GPU context, shared by windowed and headless applications
+pub struct GPUContext {
+    pub instance: Instance,
+    pub adapter: Adapter,
+    // In an Arc so that strict validation can be looked up by its address
+    pub device: Arc<Device>,
+    pub queue: Queue,
+    // Required features and the optional features supported by the adapter
+    pub granted_features: Features,
+}
+
+impl GPUContext {
+    pub async fn new(
+        selection: &AdapterSelection,
+        requirements: &DeviceRequirements,
+        trace: &ApiTrace,
+    ) -> Result<Self, FrameworkError> {
+        let instance: Instance = selection.create_instance();
+        Self::new_with_instance(instance, selection, requirements, trace, None).await
+    }
+
+    pub fn new_blocking(
+        selection: &AdapterSelection,
+        requirements: &DeviceRequirements,
+        trace: &ApiTrace,
+    ) -> Result<Self, FrameworkError> {
+        pollster::block_on(Self::new(selection, requirements, trace))
+    }
+
+    // A compatible surface must be given when the context will later be used
+    // to present to a window, so that the chosen adapter can render to it
+    pub async fn new_with_instance(
+        instance: Instance,
+        selection: &AdapterSelection,
+        requirements: &DeviceRequirements,
+        trace: &ApiTrace,
+        compatible_surface: Option<&Surface<'_>>,
+    ) -> Result<Self, FrameworkError> {
+        // Initialise adapter
+        let adapter: Adapter = selection
+            .select_adapter(&instance, compatible_surface)
+            .await
+            .ok_or(FrameworkError::AdapterNotFound)?;
+
+        // Negotiate features and limits
+        let missing_features: Features = requirements.missing_features(&adapter);
+        if !missing_features.is_empty() {
+            return Err(FrameworkError::MissingFeatures(missing_features));
+        }
+        let unsupported_limits: Vec<(&str, u64, u64)> = requirements.unsupported_limits(&adapter);
+        if !unsupported_limits.is_empty() {
+            return Err(FrameworkError::UnsupportedLimits(unsupported_limits));
+        }
+        let granted_features: Features = requirements.granted_features(&adapter);
+        log::debug!("Granted features: {granted_features:?}");
+
+        // Create logical device and command queue
+        let trace_directory: Option<PathBuf> = trace.create_run_directory();
+        let (device, queue): (Device, Queue) = adapter
+            .request_device(
+                &DeviceDescriptor {
+                    label: None,
+                    required_features: granted_features,
+                    required_limits: requirements.required_limits.clone(),
+                },
+                trace_directory.as_deref(),
+            )
+            .await?;
+
+        let context: Self = Self {
+            instance,
+            adapter,
+            device: Arc::new(device),
+            queue,
+            granted_features,
+        };
+        context.info().log();
+        Ok(context)
+    }
+
+    pub fn info(&self) -> GpuInfo {
+        GpuInfo::from_context(self)
+    }
+
+    pub fn has_feature(&self, feature: Features) -> bool {
+        self.granted_features.contains(feature)
+    }
+
+    // <---- Validation ---->
+    // When strict, validation errors from resource creation on this context's device are
+    // returned as FrameworkError values instead of only being logged, other contexts are
+    // unaffected
+    pub fn set_strict_validation(&self, strict: bool) {
+        validation::set_strict_validation(&self.device, strict);
+    }
+
+    pub fn with_strict_validation(self, strict: bool) -> Self {
+        self.set_strict_validation(strict);
+        self
+    }
+
+    pub fn is_strict_validation(&self) -> bool {
+        validation::is_strict_validation(&self.device)
+    }
+}
+
+impl Drop for GPUContext {
+    fn drop(&mut self) {
+        // A later device may reuse the address
+        validation::set_strict_validation(&self.device, false);
+    }
+}
@@ -0,0 +1,89 @@
+use serde::Serialize;
+use wgpu::{
+    Adapter, AdapterInfo, Backends, DownlevelFlags, Features, Instance, InstanceDescriptor, Limits,
+};
+
+use super::gpu_context::GPUContext;
+
+// Capability report for bug reports and diagnostics
+#[derive(Clone, Debug, Serialize)]
+pub struct GpuInfo {
+    pub adapter: AdapterInfo,
+    pub features: Vec<String>,
+    pub limits: Limits,
+    pub downlevel_flags: Vec<String>,
+    pub shader_model: String,
+}
+
+impl GpuInfo {
+    // Everything the adapter supports
+    pub fn from_adapter(adapter: &Adapter) -> Self {
+        Self::new(adapter, adapter.features(), adapter.limits())
+    }
+
+    // What the device was actually created with
+    pub fn from_context(context: &GPUContext) -> Self {
+        Self::new(
+            &context.adapter,
+            context.device.features(),
+            context.device.limits(),
+        )
+    }
+
+    fn new(adapter: &Adapter, features: Features, limits: Limits) -> Self {
+        let downlevel_flags: DownlevelFlags = adapter.get_downlevel_capabilities().flags;
+        Self {
+            adapter: adapter.get_info(),
+            features: features
+                .iter_names()
+                .map(|(name, _)| name.to_owned())
+                .collect(),
+            limits,
+            downlevel_flags: downlevel_flags
+                .iter_names()
+                .map(|(name, _)| name.to_owned())
+                .collect(),
+            shader_model: format!("{:?}", adapter.get_downlevel_capabilities().shader_model),
+        }
+    }
+
+    pub fn log(&self) {
+        log::info!(
+            "GPU: {} ({:?}, {:?} backend)",
+            self.adapter.name,
+            self.adapter.device_type,
+            self.adapter.backend
+        );
+        log::info!(
+            "  Vendor: {:#06x}, device: {:#06x}",
+            self.adapter.vendor,
+            self.adapter.device
+        );
+        log::info!(
+            "  Driver: {} {}",
+            self.adapter.driver,
+            self.adapter.driver_info
+        );
+        log::info!("  Shader model: {}", self.shader_model);
+        log::info!("  Features: {}", self.features.join(", "));
+        log::debug!("  Downlevel flags: {}", self.downlevel_flags.join(", "));
+        log::debug!("  Limits: {:#?}", self.limits);
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+// Reports every adapter available on the machine
+pub fn enumerate_gpus(backends: Backends) -> Vec<GpuInfo> {
+    let instance: Instance = Instance::new(InstanceDescriptor {
+        backends,
+        ..Default::default()
+    });
+    instance
+        .enumerate_adapters(backends)
+        .iter()
+        .map(GpuInfo::from_adapter)
+        .collect()
+}
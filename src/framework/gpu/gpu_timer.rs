@@ -0,0 +1,127 @@
+use wgpu::{
+    BufferUsages, CommandEncoder, CommandEncoderDescriptor, ComputePassDescriptor,
+    ComputePassTimestampWrites, Device, Features, QuerySet, QuerySetDescriptor, QueryType, Queue,
+    QUERY_SET_MAX_QUERIES,
+};
+
+use crate::framework::error::FrameworkError;
+
+use super::{gpu_buffer::GpuBuffer, validation::validate};
+
+// Measures the GPU time of recorded work with timestamp queries
+// -> Needs the TIMESTAMP_QUERY feature, request it as an optional feature and check
+//    is_supported() before creating a timer
+// -> Each interval is bracketed by empty compute passes writing a timestamp, so any work
+//    recorded in between is measured, whatever passes it records
+// -> Queries keep their value across submissions, intervals can be recorded into separate
+//    encoders and read together at the end
+pub struct GpuTimer {
+    query_set: QuerySet,
+    timestamps: GpuBuffer<u64>,
+    capacity: u32,
+}
+
+impl GpuTimer {
+    // Intervals a single timer can hold
+    pub const MAX_CAPACITY: u32 = QUERY_SET_MAX_QUERIES / 2;
+
+    pub fn is_supported(device: &Device) -> bool {
+        device.features().contains(Features::TIMESTAMP_QUERY)
+    }
+
+    // Timer of up to `capacity` intervals
+    #[track_caller]
+    pub fn new(
+        device: &Device,
+        capacity: u32,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        if !Self::is_supported(device) {
+            return Err(FrameworkError::MissingFeatures(Features::TIMESTAMP_QUERY));
+        }
+        let capacity: u32 = capacity.max(1);
+        if capacity > Self::MAX_CAPACITY {
+            return Err(FrameworkError::DimensionMismatch(format!(
+                "GPU timer of {capacity} intervals, at most {} are supported",
+                Self::MAX_CAPACITY
+            )));
+        }
+        let query_count: u32 = capacity * 2;
+        let query_set: QuerySet = validate(device, label, || {
+            device.create_query_set(&QuerySetDescriptor {
+                label,
+                ty: QueryType::Timestamp,
+                count: query_count,
+            })
+        })?;
+        Ok(Self {
+            query_set,
+            timestamps: GpuBuffer::zeroed(
+                device,
+                query_count as usize,
+                BufferUsages::QUERY_RESOLVE,
+                label,
+            )?,
+            capacity,
+        })
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // <---- Recording ---->
+    pub fn record_begin(&self, encoder: &mut CommandEncoder, interval: u32) {
+        self.record_timestamp(encoder, interval * 2);
+    }
+
+    pub fn record_end(&self, encoder: &mut CommandEncoder, interval: u32) {
+        self.record_timestamp(encoder, interval * 2 + 1);
+    }
+
+    fn record_timestamp(&self, encoder: &mut CommandEncoder, query: u32) {
+        assert!(
+            query < self.capacity * 2,
+            "GPU timer interval {} out of {}",
+            query / 2,
+            self.capacity
+        );
+        encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("gpu_timer_timestamp"),
+            timestamp_writes: Some(ComputePassTimestampWrites {
+                query_set: &self.query_set,
+                beginning_of_pass_write_index: Some(query),
+                end_of_pass_write_index: None,
+            }),
+        });
+    }
+
+    // <---- Reading ---->
+    // Nanoseconds elapsed in each of the first `count` intervals, blocks until available
+    #[track_caller]
+    pub fn read(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        count: u32,
+    ) -> Result<Vec<f64>, FrameworkError> {
+        let count: u32 = count.min(self.capacity);
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("gpu_timer_encoder"),
+            });
+        encoder.resolve_query_set(&self.query_set, 0..count * 2, self.timestamps.buffer(), 0);
+        queue.submit(Some(encoder.finish()));
+
+        // Timestamps are in ticks of a period given in nanoseconds
+        let period: f64 = queue.get_timestamp_period() as f64;
+        let timestamps: Vec<u64> = self.timestamps.read(device, queue)?;
+        Ok(timestamps[..count as usize * 2]
+            .chunks_exact(2)
+            .map(|interval| interval[1].saturating_sub(interval[0]) as f64 * period)
+            .collect())
+    }
+}
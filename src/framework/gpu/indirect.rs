@@ -0,0 +1,305 @@
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{Buffer, BufferUsages, Device, Queue};
+
+use crate::framework::error::FrameworkError;
+
+use super::gpu_buffer::GpuBuffer;
+
+// Arguments of an indirect command, laid out as wgpu reads them from the buffer
+// -> WGSL_STRUCT declares the matching struct for shaders writing the arguments, e.g. as
+//    array<DispatchIndirectArgs> in a read_write storage buffer
+pub trait IndirectArgs: Pod {
+    const WGSL_STRUCT: &'static str;
+}
+
+// Workgroup counts of ComputePass::dispatch_workgroups_indirect
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct DispatchIndirectArgs {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+impl DispatchIndirectArgs {
+    pub fn new(x: u32, y: u32, z: u32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl IndirectArgs for DispatchIndirectArgs {
+    const WGSL_STRUCT: &'static str = "struct DispatchIndirectArgs {
+    x: u32,
+    y: u32,
+    z: u32,
+}
+";
+}
+
+// Arguments of RenderPass::draw_indirect
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct DrawIndirectArgs {
+    pub vertex_count: u32,
+    pub instance_count: u32,
+    pub first_vertex: u32,
+    pub first_instance: u32,
+}
+
+impl DrawIndirectArgs {
+    pub fn new(vertex_count: u32, instance_count: u32) -> Self {
+        Self {
+            vertex_count,
+            instance_count,
+            ..Default::default()
+        }
+    }
+}
+
+impl IndirectArgs for DrawIndirectArgs {
+    const WGSL_STRUCT: &'static str = "struct DrawIndirectArgs {
+    vertex_count: u32,
+    instance_count: u32,
+    first_vertex: u32,
+    first_instance: u32,
+}
+";
+}
+
+// Arguments of RenderPass::draw_indexed_indirect
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct DrawIndexedIndirectArgs {
+    pub index_count: u32,
+    pub instance_count: u32,
+    pub first_index: u32,
+    pub base_vertex: i32,
+    pub first_instance: u32,
+}
+
+impl DrawIndexedIndirectArgs {
+    pub fn new(index_count: u32, instance_count: u32) -> Self {
+        Self {
+            index_count,
+            instance_count,
+            ..Default::default()
+        }
+    }
+}
+
+impl IndirectArgs for DrawIndexedIndirectArgs {
+    const WGSL_STRUCT: &'static str = "struct DrawIndexedIndirectArgs {
+    index_count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+}
+";
+}
+
+// Buffer of indirect command arguments, also usable as a storage buffer so that a compute
+// pass can write the arguments of later dispatches and draws without a CPU round-trip
+// -> first_instance must stay 0 unless the INDIRECT_FIRST_INSTANCE feature is enabled
+pub struct IndirectBuffer<A: IndirectArgs> {
+    data: GpuBuffer<A>,
+}
+
+impl<A: IndirectArgs> IndirectBuffer<A> {
+    #[track_caller]
+    pub fn new(device: &Device, args: &[A], label: Option<&str>) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            data: GpuBuffer::new(
+                device,
+                args,
+                BufferUsages::INDIRECT | BufferUsages::STORAGE,
+                label,
+            )?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        self.data.buffer()
+    }
+
+    // Byte offset of the arguments at `index`, as passed to the indirect commands
+    pub fn offset(&self, index: usize) -> u64 {
+        (index * size_of::<A>()) as u64
+    }
+
+    pub fn write(&self, queue: &Queue, index: usize, args: &[A]) -> Result<(), FrameworkError> {
+        self.data.write_range(queue, index, args)
+    }
+
+    // Blocks until the contents are available
+    #[track_caller]
+    pub fn read(&self, device: &Device, queue: &Queue) -> Result<Vec<A>, FrameworkError> {
+        self.data.read(device, queue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+
+    use wgpu::{Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor, ComputePass};
+
+    use crate::framework::{
+        compute_app::app::{ComputeApp, Dispatch},
+        error::FrameworkError,
+        gpu::{
+            compute_graph::{ComputeGraph, GraphPass, PassDispatch},
+            compute_kernel::ComputeKernel,
+            gpu_buffer::GpuBuffer,
+            gpu_context::GPUContext,
+            utilities::create_buffer,
+        },
+        verification::verification_context,
+    };
+
+    use super::{
+        DispatchIndirectArgs, DrawIndexedIndirectArgs, DrawIndirectArgs, IndirectArgs,
+        IndirectBuffer,
+    };
+
+    // plan writes (3, 2, 1) workgroups at index 1, count counts its 4 invocations per workgroup
+    fn counting_wgsl() -> String {
+        format!(
+            "{}
+@group(0) @binding(0) var<storage, read_write> args: array<DispatchIndirectArgs>;
+@group(0) @binding(1) var<storage, read_write> counter: atomic<u32>;
+
+@compute @workgroup_size(1)
+fn plan() {{
+    args[1] = DispatchIndirectArgs(3u, 2u, 1u);
+}}
+
+@compute @workgroup_size(4)
+fn count() {{
+    atomicAdd(&counter, 1u);
+}}
+",
+            DispatchIndirectArgs::WGSL_STRUCT
+        )
+    }
+    const INVOCATIONS: u32 = 3 * 2 * 4;
+
+    #[track_caller]
+    fn assert_wgsl_size<A: IndirectArgs>(name: &str) {
+        let module: naga::Module = naga::front::wgsl::parse_str(A::WGSL_STRUCT).unwrap();
+        let span: Option<u32> = module.types.iter().find_map(|(_, ty)| match &ty.inner {
+            naga::TypeInner::Struct { span, .. } if ty.name.as_deref() == Some(name) => Some(*span),
+            _ => None,
+        });
+        assert_eq!(span, Some(size_of::<A>() as u32), "{name}");
+    }
+
+    #[test]
+    fn wgsl_structs_match_args() {
+        assert_wgsl_size::<DispatchIndirectArgs>("DispatchIndirectArgs");
+        assert_wgsl_size::<DrawIndirectArgs>("DrawIndirectArgs");
+        assert_wgsl_size::<DrawIndexedIndirectArgs>("DrawIndexedIndirectArgs");
+    }
+
+    #[test]
+    fn kernel_dispatches_from_written_args() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let (device, queue) = (&context.device, &context.queue);
+        let source: String = counting_wgsl();
+        let args: IndirectBuffer<DispatchIndirectArgs> =
+            IndirectBuffer::new(device, &[DispatchIndirectArgs::default(); 2], Some("args"))?;
+        let counter: GpuBuffer<u32> = GpuBuffer::storage(device, &[0], Some("counter"))?;
+        let plan: ComputeKernel = ComputeKernel::builder(&source, "plan")
+            .with_buffer(0, args.buffer())
+            .build(device)?;
+        let count: ComputeKernel = ComputeKernel::builder(&source, "count")
+            .with_buffer(1, counter.buffer())
+            .build(device)?;
+
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor { label: None });
+        {
+            let mut compute_pass: ComputePass = encoder.begin_compute_pass(&Default::default());
+            plan.dispatch(&mut compute_pass, (1, 1, 1));
+            count.dispatch_indirect(&mut compute_pass, &args, 1);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        assert_eq!(
+            args.read(device, queue)?,
+            [
+                DispatchIndirectArgs::default(),
+                DispatchIndirectArgs::new(3, 2, 1)
+            ]
+        );
+        assert_eq!(counter.read(device, queue)?, [INVOCATIONS]);
+        Ok(())
+    }
+
+    #[test]
+    fn graph_dispatches_from_written_args() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let (device, queue) = (&context.device, &context.queue);
+        let source: String = counting_wgsl();
+        let args: Buffer = create_buffer(
+            device,
+            &[0; 2 * size_of::<DispatchIndirectArgs>()],
+            BufferUsages::INDIRECT | BufferUsages::STORAGE,
+            None,
+        )?;
+        let counter: Buffer = create_buffer(
+            device,
+            &[0; 4],
+            BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            None,
+        )?;
+        let mut graph: ComputeGraph = ComputeGraph::new();
+        graph.add_buffer("args", args);
+        graph.add_buffer("counter", counter);
+        // Ordered after plan as it reads the arguments
+        graph.add_pass(
+            GraphPass::new("count", &source, "count")
+                .with_write(1, "counter")
+                .with_dispatch(PassDispatch::Indirect {
+                    buffer: "args",
+                    index: 1,
+                }),
+        );
+        graph.add_pass(GraphPass::new("plan", &source, "plan").with_write(0, "args"));
+
+        graph.run(device, queue, 2)?;
+        assert_eq!(graph.order(), ["plan", "count"]);
+        assert_eq!(
+            graph.read::<u32>(device, queue, "counter")?,
+            [2 * INVOCATIONS]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn compute_app_dispatches_from_written_args() -> Result<(), FrameworkError> {
+        let mut app: ComputeApp = ComputeApp::new_with_context(verification_context()?);
+        let source: String = counting_wgsl();
+        app.add_indirect_buffer("args", 0, &[DispatchIndirectArgs::default(); 2])?;
+        app.add_storage_buffer("counter", 1, &[0u32])?;
+        app.add_kernel("plan", &source, "plan", &["args"])?;
+        app.add_kernel("count", &source, "count", &["counter"])?;
+
+        app.run(&[
+            Dispatch::new("plan", (1, 1, 1)),
+            Dispatch::indirect("count", "args", 1),
+        ])?;
+        assert_eq!(app.read_buffer::<u32>("counter")?, [INVOCATIONS]);
+        Ok(())
+    }
+}
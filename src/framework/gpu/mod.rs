@@ -0,0 +1,14 @@
+pub mod adapter_selection;
+pub mod api_trace;
+pub mod compute_graph;
+pub mod compute_kernel;
+pub mod device_requirements;
+pub mod gpu_buffer;
+pub mod gpu_context;
+pub mod gpu_info;
+pub mod gpu_timer;
+pub mod indirect;
+pub mod readback;
+pub mod scalar;
+pub mod utilities;
+pub mod validation;
@@ -0,0 +1,411 @@
+use std::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use bytemuck::Pod;
+use wgpu::{
+    Buffer, BufferAddress, BufferAsyncError, BufferUsages, CommandEncoder,
+    CommandEncoderDescriptor, Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout,
+    Maintain, MapMode, Origin3d, Queue, Texture, TextureAspect, COPY_BUFFER_ALIGNMENT,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+use crate::framework::error::FrameworkError;
+
+use super::utilities::create_empty_buffer;
+
+// Copy of a buffer or texture into a staging buffer, read back once mapped
+// -> Copies can be recorded into any encoder, map() must be called once it is submitted
+// -> Each readback owns its staging buffer, so any number can be in flight at once
+// -> Map callbacks only run when the device is polled, either by blocking with
+//    wait() or by calling Device::poll(Maintain::Poll) regularly (e.g. once per frame)
+pub struct Readback {
+    staging_buffer: Buffer,
+    // Bytes of the staging buffer holding copied data
+    size: BufferAddress,
+    rows: Option<RowLayout>,
+    state: Arc<Mutex<MapState>>,
+}
+
+// Texture rows are padded to COPY_BYTES_PER_ROW_ALIGNMENT in the staging buffer
+#[derive(Clone, Copy, Debug)]
+struct RowLayout {
+    unpadded_bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    row_count: u32,
+}
+
+#[derive(Default)]
+struct MapState {
+    mapping: bool,
+    result: Option<Result<(), BufferAsyncError>>,
+    taken: bool,
+    waker: Option<Waker>,
+}
+
+impl Readback {
+    // <---- Recording ---->
+    // The buffer must have the COPY_SRC usage
+    #[track_caller]
+    pub fn record_buffer(
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        buffer: &Buffer,
+    ) -> Result<Self, FrameworkError> {
+        Self::record_buffer_range(device, encoder, buffer, 0, buffer.size())
+    }
+
+    // Offset and size must be multiples of 4
+    #[track_caller]
+    pub fn record_buffer_range(
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        buffer: &Buffer,
+        offset: BufferAddress,
+        size: BufferAddress,
+    ) -> Result<Self, FrameworkError> {
+        // Staging buffers can't be empty
+        let staging_buffer: Buffer =
+            create_staging_buffer(device, size.max(COPY_BUFFER_ALIGNMENT))?;
+        if size > 0 {
+            encoder.copy_buffer_to_buffer(buffer, offset, &staging_buffer, 0, size);
+        }
+        Ok(Self {
+            staging_buffer,
+            size,
+            rows: None,
+            state: Default::default(),
+        })
+    }
+
+    // Copies mip level 0 of a 2D texture, which must have the COPY_SRC usage
+    #[track_caller]
+    pub fn record_texture(
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+    ) -> Result<Self, FrameworkError> {
+        let bytes_per_pixel: u32 = texture
+            .format()
+            .block_copy_size(Some(TextureAspect::All))
+            .ok_or(FrameworkError::UnsupportedTextureFormat(texture.format()))?;
+        let unpadded_bytes_per_row: u32 = texture.width() * bytes_per_pixel;
+        let rows: RowLayout = RowLayout {
+            unpadded_bytes_per_row,
+            padded_bytes_per_row: unpadded_bytes_per_row
+                .next_multiple_of(COPY_BYTES_PER_ROW_ALIGNMENT),
+            row_count: texture.height(),
+        };
+
+        let size: BufferAddress = (rows.padded_bytes_per_row * rows.row_count) as BufferAddress;
+        let staging_buffer: Buffer = create_staging_buffer(device, size)?;
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            ImageCopyBuffer {
+                buffer: &staging_buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(rows.padded_bytes_per_row),
+                    rows_per_image: Some(rows.row_count),
+                },
+            },
+            Extent3d {
+                width: texture.width(),
+                height: texture.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+        Ok(Self {
+            staging_buffer,
+            size,
+            rows: Some(rows),
+            state: Default::default(),
+        })
+    }
+
+    // Records, submits and maps a buffer copy
+    #[track_caller]
+    pub fn buffer(device: &Device, queue: &Queue, buffer: &Buffer) -> Result<Self, FrameworkError> {
+        let mut encoder: CommandEncoder = create_readback_encoder(device);
+        let readback: Self = Self::record_buffer(device, &mut encoder, buffer)?;
+        queue.submit(Some(encoder.finish()));
+        readback.map();
+        Ok(readback)
+    }
+
+    // Records, submits and maps a texture copy
+    #[track_caller]
+    pub fn texture(
+        device: &Device,
+        queue: &Queue,
+        texture: &Texture,
+    ) -> Result<Self, FrameworkError> {
+        let mut encoder: CommandEncoder = create_readback_encoder(device);
+        let readback: Self = Self::record_texture(device, &mut encoder, texture)?;
+        queue.submit(Some(encoder.finish()));
+        readback.map();
+        Ok(readback)
+    }
+
+    // <---- Mapping ---->
+    // Requests the staging buffer mapping, only call after the copy was submitted
+    pub fn map(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.mapping {
+            return;
+        }
+        state.mapping = true;
+        drop(state);
+
+        let map_state: Arc<Mutex<MapState>> = self.state.clone();
+        self.staging_buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                let mut state = map_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.state.lock().unwrap().result.is_some()
+    }
+
+    // <---- Reading ---->
+    // Polls the device without blocking, returns the contents once (when mapped)
+    pub fn try_read<T: Pod>(&self, device: &Device) -> Option<Result<Vec<T>, FrameworkError>> {
+        self.map();
+        device.poll(Maintain::Poll);
+        self.take_contents()
+    }
+
+    // Blocks until the contents are available
+    pub fn wait<T: Pod>(self, device: &Device) -> Result<Vec<T>, FrameworkError> {
+        self.map();
+        device.poll(Maintain::Wait);
+        pollster::block_on(self.into_future())
+    }
+
+    // Resolves once the device has been polled after the copy completed
+    pub fn into_future<T: Pod>(self) -> ReadbackFuture<T> {
+        self.map();
+        ReadbackFuture {
+            readback: self,
+            _element: PhantomData,
+        }
+    }
+
+    fn take_contents<T: Pod>(&self) -> Option<Result<Vec<T>, FrameworkError>> {
+        let mut state = self.state.lock().unwrap();
+        if state.taken {
+            return None;
+        }
+        let result: Result<(), BufferAsyncError> = state.result.clone()?;
+        state.taken = true;
+        drop(state);
+
+        if let Err(err) = result {
+            return Some(Err(err.into()));
+        }
+        let contents: Vec<T> = {
+            let mapped = self
+                .staging_buffer
+                .slice(..self.size.max(COPY_BUFFER_ALIGNMENT))
+                .get_mapped_range();
+            let mapped: &[u8] = &mapped[..self.size as usize];
+            match self.rows {
+                None => bytemuck::pod_collect_to_vec(mapped),
+                // -> Remove row padding
+                Some(rows) => {
+                    let bytes: Vec<u8> = mapped
+                        .chunks(rows.padded_bytes_per_row as usize)
+                        .flat_map(|row| &row[..rows.unpadded_bytes_per_row as usize])
+                        .copied()
+                        .collect();
+                    bytemuck::pod_collect_to_vec(&bytes)
+                }
+            }
+        };
+        self.staging_buffer.unmap();
+        Some(Ok(contents))
+    }
+}
+
+pub struct ReadbackFuture<T> {
+    readback: Readback,
+    _element: PhantomData<fn() -> T>,
+}
+
+impl<T: Pod> Future for ReadbackFuture<T> {
+    type Output = Result<Vec<T>, FrameworkError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if let Some(contents) = self.readback.take_contents() {
+            return Poll::Ready(contents);
+        }
+        // Register waker, then check again in case the map completed meanwhile
+        self.readback.state.lock().unwrap().waker = Some(cx.waker().clone());
+        match self.readback.take_contents() {
+            Some(contents) => Poll::Ready(contents),
+            None => Poll::Pending,
+        }
+    }
+}
+
+#[track_caller]
+fn create_staging_buffer(device: &Device, size: BufferAddress) -> Result<Buffer, FrameworkError> {
+    create_empty_buffer(
+        device,
+        size,
+        BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        Some("readback_staging_buffer"),
+    )
+}
+
+fn create_readback_encoder(device: &Device) -> CommandEncoder {
+    device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("readback_encoder"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::Future,
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use wgpu::{
+        Buffer, BufferUsages, CommandEncoder, Extent3d, ImageCopyTexture, ImageDataLayout,
+        Maintain, Origin3d, Texture, TextureAspect, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureUsages,
+    };
+
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_context::GPUContext, utilities::create_buffer},
+        verification::{kernel_check::uniform_u32, verification_context},
+    };
+
+    use super::{create_readback_encoder, Readback};
+
+    // Uploads `width` x `height` texels of 4 or 16 bytes, as words
+    fn texture_with_contents(
+        context: &GPUContext,
+        format: TextureFormat,
+        width: u32,
+        height: u32,
+        contents: &[u32],
+    ) -> Texture {
+        let size: Extent3d = Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture: Texture = context.device.create_texture(&TextureDescriptor {
+            label: Some("readback_test"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage: TextureUsages::COPY_SRC | TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        context.queue.write_texture(
+            ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            bytemuck::cast_slice(contents),
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(contents.len() as u32 * 4 / height),
+                rows_per_image: Some(height),
+            },
+            size,
+        );
+        texture
+    }
+
+    #[test]
+    fn texture_rows_are_unpadded() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let (device, queue) = (&context.device, &context.queue);
+        // Rows of 12 and 48 bytes, padded to 256 in the staging buffer
+        for (format, words_per_texel) in [
+            (TextureFormat::Rgba8Unorm, 1),
+            (TextureFormat::Rgba32Float, 4),
+        ] {
+            let (width, height): (u32, u32) = (3, 5);
+            // Float bits of small integers, so no NaN is canonicalised on the way
+            let contents: Vec<u32> = (0..width * height * words_per_texel)
+                .map(|value| match format {
+                    TextureFormat::Rgba32Float => (value as f32).to_bits(),
+                    _ => value * 0x0101_0101,
+                })
+                .collect();
+            let texture: Texture =
+                texture_with_contents(&context, format, width, height, &contents);
+            let readback: Readback = Readback::texture(device, queue, &texture)?;
+            assert_eq!(readback.wait::<u32>(device)?, contents, "{format:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn contents_are_available_once_mapped() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let (device, queue) = (&context.device, &context.queue);
+        let contents: Vec<u32> = uniform_u32(1, 1000);
+        let buffer: Buffer = create_buffer(
+            device,
+            bytemuck::cast_slice(&contents),
+            BufferUsages::COPY_SRC,
+            None,
+        )?;
+
+        // Map callbacks only run once the device is polled
+        let mut encoder: CommandEncoder = create_readback_encoder(device);
+        let readback: Readback = Readback::record_buffer(device, &mut encoder, &buffer)?;
+        queue.submit(Some(encoder.finish()));
+        readback.map();
+        assert!(!readback.is_ready());
+        assert!(readback.take_contents::<u32>().is_none());
+
+        device.poll(Maintain::Wait);
+        assert!(readback.is_ready());
+        assert_eq!(
+            readback.try_read::<u32>(device).transpose()?,
+            Some(contents.clone())
+        );
+        // Contents are only returned once
+        assert!(readback.try_read::<u32>(device).is_none());
+
+        // Futures are pending until then too
+        let readback: Readback = Readback::buffer(device, queue, &buffer)?;
+        let mut future = pin!(readback.into_future::<u32>());
+        let mut waker_context: Context = Context::from_waker(Waker::noop());
+        assert!(future.as_mut().poll(&mut waker_context).is_pending());
+        device.poll(Maintain::Wait);
+        match future.as_mut().poll(&mut waker_context) {
+            Poll::Ready(result) => assert_eq!(result?, contents),
+            Poll::Pending => panic!("Readback still pending after waiting on the device"),
+        }
+        Ok(())
+    }
+}
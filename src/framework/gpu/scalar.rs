@@ -0,0 +1,78 @@
+use std::fmt::Debug;
+
+use bytemuck::Pod;
+
+// Scalar types usable as elements of storage buffers in generic WGSL kernels
+// -> Integer arithmetic wraps on overflow, as it does in WGSL
+pub trait GpuScalar: Pod + PartialOrd + Debug + Send + Sync {
+    // Name of the type in WGSL
+    const WGSL_TYPE: &'static str;
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+    // Identities of max and min, the infinities for floats
+    const LOWEST: Self;
+    const HIGHEST: Self;
+
+    // WGSL expression evaluating to exactly this value
+    fn to_wgsl(self) -> String;
+
+    fn add(self, other: Self) -> Self;
+}
+
+impl GpuScalar for u32 {
+    const WGSL_TYPE: &'static str = "u32";
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+    const MIN: Self = u32::MIN;
+    const MAX: Self = u32::MAX;
+    const LOWEST: Self = u32::MIN;
+    const HIGHEST: Self = u32::MAX;
+
+    fn to_wgsl(self) -> String {
+        format!("{self}u")
+    }
+
+    fn add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+}
+
+impl GpuScalar for i32 {
+    const WGSL_TYPE: &'static str = "i32";
+    const ZERO: Self = 0;
+    const ONE: Self = 1;
+    const MIN: Self = i32::MIN;
+    const MAX: Self = i32::MAX;
+    const LOWEST: Self = i32::MIN;
+    const HIGHEST: Self = i32::MAX;
+
+    // -2147483648i can't be written as a literal, as it is parsed as a negated 2147483648i
+    fn to_wgsl(self) -> String {
+        format!("bitcast<i32>({}u)", self as u32)
+    }
+
+    fn add(self, other: Self) -> Self {
+        self.wrapping_add(other)
+    }
+}
+
+impl GpuScalar for f32 {
+    const WGSL_TYPE: &'static str = "f32";
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const MIN: Self = f32::MIN;
+    const MAX: Self = f32::MAX;
+    const LOWEST: Self = f32::NEG_INFINITY;
+    const HIGHEST: Self = f32::INFINITY;
+
+    // Bit pattern, so no precision is lost by printing
+    fn to_wgsl(self) -> String {
+        format!("bitcast<f32>({}u)", self.to_bits())
+    }
+
+    fn add(self, other: Self) -> Self {
+        self + other
+    }
+}
@@ -1,24 +1,31 @@
-#![allow(dead_code)]
+use std::borrow::Cow;
 
+use rustc_hash::FxHashMap;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
     BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
-    BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferUsages, CommandEncoder,
-    ComputePipeline, ComputePipelineDescriptor, Device, FragmentState, MultisampleState,
-    PipelineCompilationOptions, PipelineLayout, PipelineLayoutDescriptor, PrimitiveState,
-    RenderPass, RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderStages, TextureView,
-    VertexState,
+    BindGroupLayoutEntry, BindingType, Buffer, BufferBindingType, BufferDescriptor, BufferUsages,
+    CommandEncoder, ComputePipeline, ComputePipelineDescriptor, Device, FragmentState,
+    MultisampleState, PipelineCompilationOptions, PipelineLayout, PipelineLayoutDescriptor,
+    PrimitiveState, Queue, RenderPass, RenderPipeline, RenderPipelineDescriptor, ShaderModule,
+    ShaderModuleDescriptor, ShaderSource, ShaderStages, TextureView, VertexState,
 };
 
-use crate::framework::windowed_app::rendering::renderer::BufferMap;
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{readback::Readback, validation::validate},
+};
+
+pub type BufferMap = FxHashMap<&'static str, (u32, Buffer)>;
 
 // <---- Bind groups ---->
+#[track_caller]
 pub fn create_bind_group(
     device: &Device,
     buffers: &BufferMap,
     bind_group_layout: &BindGroupLayout,
     label: Option<&str>,
-) -> BindGroup {
+) -> Result<BindGroup, FrameworkError> {
     let entries: &[BindGroupEntry] = &buffers
         .iter()
         .map(|(_label, (binding, buffer))| BindGroupEntry {
@@ -27,26 +34,47 @@ pub fn create_bind_group(
         })
         .collect::<Vec<BindGroupEntry>>();
 
-    device.create_bind_group(&BindGroupDescriptor {
-        layout: bind_group_layout,
-        entries,
-        label,
+    validate(device, label, || {
+        device.create_bind_group(&BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries,
+            label,
+        })
+    })
+}
+
+#[track_caller]
+pub fn create_bind_group_with_entries(
+    device: &Device,
+    entries: &[BindGroupEntry],
+    bind_group_layout: &BindGroupLayout,
+    label: Option<&str>,
+) -> Result<BindGroup, FrameworkError> {
+    validate(device, label, || {
+        device.create_bind_group(&BindGroupDescriptor {
+            layout: bind_group_layout,
+            entries,
+            label,
+        })
     })
 }
 
-pub fn create_bind_group_entry(binding: u32, buffer: &Buffer) -> BindGroupEntry {
+pub fn create_bind_group_entry(binding: u32, buffer: &Buffer) -> BindGroupEntry<'_> {
     BindGroupEntry {
         binding,
         resource: buffer.as_entire_binding(),
     }
 }
 
+#[track_caller]
 pub fn create_bind_group_layout(
     device: &Device,
     entries: &[BindGroupLayoutEntry],
     label: Option<&str>,
-) -> BindGroupLayout {
-    device.create_bind_group_layout(&BindGroupLayoutDescriptor { label, entries })
+) -> Result<BindGroupLayout, FrameworkError> {
+    validate(device, label, || {
+        device.create_bind_group_layout(&BindGroupLayoutDescriptor { label, entries })
+    })
 }
 
 pub fn create_bind_group_layout_entry(
@@ -111,19 +139,50 @@ pub fn create_buffer_binding_type(
 
 //======================================================================
 // <---- Buffers ---->
+#[track_caller]
 pub fn create_buffer(
     device: &Device,
     contents: &[u8],
     usage: BufferUsages,
     label: Option<&str>,
-) -> Buffer {
-    device.create_buffer_init(&BufferInitDescriptor {
-        label,
-        contents,
-        usage,
+) -> Result<Buffer, FrameworkError> {
+    validate(device, label, || {
+        device.create_buffer_init(&BufferInitDescriptor {
+            label,
+            contents,
+            usage,
+        })
     })
 }
 
+#[track_caller]
+pub fn create_empty_buffer(
+    device: &Device,
+    size: u64,
+    usage: BufferUsages,
+    label: Option<&str>,
+) -> Result<Buffer, FrameworkError> {
+    validate(device, label, || {
+        device.create_buffer(&BufferDescriptor {
+            label,
+            size,
+            usage,
+            mapped_at_creation: false,
+        })
+    })
+}
+
+// Copies a buffer into a staging buffer and blocks until its contents are mapped
+// -> The source buffer must have the COPY_SRC usage
+#[track_caller]
+pub fn read_buffer(
+    device: &Device,
+    queue: &Queue,
+    buffer: &Buffer,
+) -> Result<Vec<u8>, FrameworkError> {
+    Readback::buffer(device, queue, buffer)?.wait(device)
+}
+
 pub fn add_buffer(map: &mut BufferMap, buffer: Buffer, binding: u32, label: &'static str) {
     map.insert(label, (binding, buffer));
 }
@@ -138,50 +197,94 @@ pub fn get_buffer_usage(map: &BufferMap, label: &str) -> BufferUsages {
 
 //======================================================================
 // <---- Pipelines ---->
+#[track_caller]
 pub fn create_render_pipeline(
     device: &Device,
     layout: &PipelineLayout,
     vertex: VertexState,
     fragment: FragmentState,
     label: Option<&str>,
-) -> RenderPipeline {
-    device.create_render_pipeline(&RenderPipelineDescriptor {
-        label,
-        layout: Some(layout),
-        vertex,
-        fragment: Some(fragment),
-        primitive: PrimitiveState::default(),
-        depth_stencil: None,
-        multiview: None,
-        multisample: MultisampleState::default(),
+) -> Result<RenderPipeline, FrameworkError> {
+    validate(device, label, || {
+        device.create_render_pipeline(&RenderPipelineDescriptor {
+            label,
+            layout: Some(layout),
+            vertex,
+            fragment: Some(fragment),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multiview: None,
+            multisample: MultisampleState::default(),
+        })
     })
 }
 
+#[track_caller]
 pub fn create_compute_pipeline(
     device: &Device,
     layout: &PipelineLayout,
     module: &ShaderModule,
     entry_point: &str,
     label: Option<&str>,
-) -> ComputePipeline {
-    device.create_compute_pipeline(&ComputePipelineDescriptor {
-        label,
-        layout: Some(layout),
-        module,
-        entry_point,
-        compilation_options: PipelineCompilationOptions::default(),
+) -> Result<ComputePipeline, FrameworkError> {
+    validate(device, label, || {
+        device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label,
+            layout: Some(layout),
+            module,
+            entry_point,
+            compilation_options: PipelineCompilationOptions::default(),
+        })
+    })
+}
+
+// Bind group layouts are derived from the shader, retrieve them with
+// ComputePipeline::get_bind_group_layout
+#[track_caller]
+pub fn create_compute_pipeline_with_derived_layout(
+    device: &Device,
+    module: &ShaderModule,
+    entry_point: &str,
+    label: Option<&str>,
+) -> Result<ComputePipeline, FrameworkError> {
+    validate(device, label, || {
+        device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label,
+            layout: None,
+            module,
+            entry_point,
+            compilation_options: PipelineCompilationOptions::default(),
+        })
     })
 }
 
+#[track_caller]
 pub fn create_pipeline_layout(
     device: &Device,
     bind_group_layouts: &[&BindGroupLayout],
     label: Option<&str>,
-) -> PipelineLayout {
-    device.create_pipeline_layout(&PipelineLayoutDescriptor {
-        label,
-        bind_group_layouts,
-        push_constant_ranges: &[],
+) -> Result<PipelineLayout, FrameworkError> {
+    validate(device, label, || {
+        device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label,
+            bind_group_layouts,
+            push_constant_ranges: &[],
+        })
+    })
+}
+
+// <---- Shaders ---->
+#[track_caller]
+pub fn create_wgsl_shader_module(
+    device: &Device,
+    source: &str,
+    label: Option<&str>,
+) -> Result<ShaderModule, FrameworkError> {
+    validate(device, label, || {
+        device.create_shader_module(ShaderModuleDescriptor {
+            label,
+            source: ShaderSource::Wgsl(Cow::Borrowed(source)),
+        })
     })
 }
 
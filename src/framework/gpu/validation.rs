@@ -0,0 +1,115 @@
+use std::{panic::Location, sync::Mutex};
+
+use wgpu::{Device, ErrorFilter};
+
+use crate::framework::error::FrameworkError;
+
+// Devices on which validation errors are returned as FrameworkError values instead of only
+// being logged, by address
+// -> Set per context with GPUContext::set_strict_validation, which keeps its device in an Arc
+//    so the address doesn't change while it is registered
+static STRICT_DEVICES: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+fn device_key(device: &Device) -> usize {
+    device as *const Device as usize
+}
+
+pub(crate) fn set_strict_validation(device: &Device, strict: bool) {
+    let key: usize = device_key(device);
+    let mut devices = STRICT_DEVICES.lock().unwrap_or_else(|err| err.into_inner());
+    devices.retain(|&device| device != key);
+    if strict {
+        devices.push(key);
+    }
+}
+
+pub fn is_strict_validation(device: &Device) -> bool {
+    STRICT_DEVICES
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .contains(&device_key(device))
+}
+
+// Runs a resource creation inside a validation error scope
+// -> The reported location is the caller of the outermost #[track_caller] function
+#[track_caller]
+pub fn validate<T>(
+    device: &Device,
+    label: Option<&str>,
+    create: impl FnOnce() -> T,
+) -> Result<T, FrameworkError> {
+    let location: &Location = Location::caller();
+    device.push_error_scope(ErrorFilter::Validation);
+    let resource: T = create();
+    match pollster::block_on(device.pop_error_scope()) {
+        None => Ok(resource),
+        Some(err) => {
+            let label: &str = label.unwrap_or("<unlabelled>");
+            log::error!("Validation error creating '{label}' at {location}: {err}");
+            match is_strict_validation(device) {
+                true => Err(FrameworkError::Validation {
+                    label: label.to_owned(),
+                    location: location.to_string(),
+                    message: err.to_string(),
+                }),
+                false => Ok(resource),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{BindGroupLayoutEntry, BindingType, BufferBindingType};
+
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{
+            adapter_selection::AdapterSelection,
+            api_trace::ApiTrace,
+            device_requirements::DeviceRequirements,
+            gpu_context::GPUContext,
+            utilities::{create_bind_group_layout, create_compute_bind_group_layout_entry},
+        },
+    };
+
+    fn software_context() -> GPUContext {
+        GPUContext::new_blocking(
+            &AdapterSelection::new().with_fallback_adapter(true),
+            &DeviceRequirements::default(),
+            &ApiTrace::new(),
+        )
+        .expect("Software adapter is available")
+    }
+
+    // Two entries at the same binding
+    fn create_invalid_layout(context: &GPUContext) -> Result<(), FrameworkError> {
+        let entry: BindGroupLayoutEntry = create_compute_bind_group_layout_entry(
+            0,
+            BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        );
+        create_bind_group_layout(&context.device, &[entry, entry], Some("invalid")).map(|_| ())
+    }
+
+    #[test]
+    fn strictness_is_per_context() {
+        let strict: GPUContext = software_context().with_strict_validation(true);
+        let lenient: GPUContext = software_context();
+        assert!(strict.is_strict_validation());
+        assert!(!lenient.is_strict_validation());
+
+        let err: FrameworkError = create_invalid_layout(&strict).unwrap_err();
+        assert!(
+            matches!(&err, FrameworkError::Validation { label, .. } if label == "invalid"),
+            "{err}"
+        );
+        assert!(create_invalid_layout(&lenient).is_ok());
+
+        strict.set_strict_validation(false);
+        assert!(create_invalid_layout(&strict).is_ok());
+    }
+}
@@ -0,0 +1,139 @@
+use bytemuck::Pod;
+
+// Bindings shared by every filter kernel, prepended to the source of custom filters:
+// -> input_image (binding 0), read with textureLoad
+// -> output_image (binding 1), written with textureStore, of the filter's output size
+// -> Custom filters with parameters read them from a uniform at binding 2
+pub const FILTER_BINDINGS_WGSL: &str = "@group(0) @binding(0) var input_image: texture_2d<f32>;
+@group(0) @binding(1) var output_image: texture_storage_2d<rgba32float, write>;
+";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorConversion {
+    // Rec. 709 luma in the three colour channels
+    RgbToGrayscale,
+    // Hue, saturation and value in [0, 1]
+    RgbToHsv,
+    HsvToRgb,
+    // BT.601 full range, chroma offset by 0.5
+    RgbToYCbCr,
+    YCbCrToRgb,
+    SrgbToLinear,
+    LinearToSrgb,
+}
+
+impl ColorConversion {
+    // Must match image_filters.wgsl
+    pub(crate) fn mode(self) -> u32 {
+        match self {
+            Self::RgbToGrayscale => 0,
+            Self::RgbToHsv => 1,
+            Self::HsvToRgb => 2,
+            Self::RgbToYCbCr => 3,
+            Self::YCbCrToRgb => 4,
+            Self::SrgbToLinear => 5,
+            Self::LinearToSrgb => 6,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResizeFilter {
+    Nearest,
+    // Interpolates the 4 closest pixels, blur first when shrinking by more than half to
+    // avoid aliasing
+    #[default]
+    Bilinear,
+}
+
+// Compute kernel applied as one step of a filter chain, one invocation per output pixel
+// -> The source is appended to FILTER_BINDINGS_WGSL, so it must not declare them itself
+#[derive(Clone, Debug)]
+pub struct CustomFilter {
+    pub(crate) source: String,
+    pub(crate) entry_point: String,
+    // Uniform contents at binding 2
+    pub(crate) params: Option<Vec<u8>>,
+    // Size of the input when None
+    pub(crate) output_size: Option<(u32, u32)>,
+}
+
+impl CustomFilter {
+    pub fn new(source: &str, entry_point: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            entry_point: entry_point.to_owned(),
+            params: None,
+            output_size: None,
+        }
+    }
+
+    pub fn with_params<P: Pod>(mut self, params: &P) -> Self {
+        self.params = Some(bytemuck::bytes_of(params).to_vec());
+        self
+    }
+
+    pub fn with_output_size(mut self, width: u32, height: u32) -> Self {
+        self.output_size = Some((width, height));
+        self
+    }
+}
+
+// Step of a filter chain
+// -> Pixels outside the input repeat its closest edge pixel
+#[derive(Clone, Debug)]
+pub enum Filter {
+    // Row-major weights of a width x height kernel centred on (width / 2, height / 2),
+    // applied as a correlation (the kernel isn't flipped) to every channel
+    Convolution {
+        weights: Vec<f32>,
+        width: u32,
+        height: u32,
+    },
+    // Separable, as a horizontal then a vertical convolution of radius ceil(3 * sigma)
+    GaussianBlur {
+        sigma: f32,
+    },
+    // Gradient magnitude of the luma in the three colour channels, alpha is kept
+    Sobel,
+    ColorConversion(ColorConversion),
+    Resize {
+        width: u32,
+        height: u32,
+        filter: ResizeFilter,
+    },
+    Custom(CustomFilter),
+}
+
+impl Filter {
+    pub fn box_blur(radius: u32) -> Self {
+        let size: u32 = radius * 2 + 1;
+        Self::Convolution {
+            weights: vec![1.0 / (size * size) as f32; (size * size) as usize],
+            width: size,
+            height: size,
+        }
+    }
+
+    pub fn sharpen() -> Self {
+        Self::Convolution {
+            weights: vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0],
+            width: 3,
+            height: 3,
+        }
+    }
+}
+
+// Normalised weights of a 1D Gaussian kernel of radius ceil(3 * sigma), a single unit weight
+// when sigma isn't positive
+pub fn gaussian_weights(sigma: f32) -> Vec<f32> {
+    if sigma <= 0.0 || sigma.is_nan() {
+        return vec![1.0];
+    }
+    let radius: i32 = (3.0 * sigma).ceil() as i32;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|offset| (-(offset * offset) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / sum).collect()
+}
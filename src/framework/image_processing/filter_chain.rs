@@ -0,0 +1,589 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    BindGroup, BindGroupEntry, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
+    BufferBindingType, BufferUsages, CommandEncoder, CommandEncoderDescriptor,
+    ComputePassDescriptor, Device, ImageCopyTexture, Origin3d, Queue, StorageTextureAccess,
+    TextureAspect, TextureSampleType, TextureViewDimension,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        compute_kernel::ComputeKernel,
+        utilities::{
+            create_bind_group_with_entries, create_buffer, create_compute_bind_group_layout_entry,
+        },
+    },
+};
+
+use super::{
+    filter::{gaussian_weights, CustomFilter, Filter, ResizeFilter, FILTER_BINDINGS_WGSL},
+    gpu_image::{GpuImage, IMAGE_FORMAT},
+};
+
+const PARAMS_BINDING: u32 = 2;
+const WEIGHTS_BINDING: u32 = 3;
+
+// Must match image_filters.wgsl
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    kernel_width: u32,
+    kernel_height: u32,
+    mode: u32,
+    _padding: u32,
+}
+
+impl Params {
+    fn kernel(kernel_width: u32, kernel_height: u32) -> Self {
+        Self {
+            kernel_width,
+            kernel_height,
+            mode: 0,
+            _padding: 0,
+        }
+    }
+
+    fn mode(mode: u32) -> Self {
+        Self {
+            kernel_width: 0,
+            kernel_height: 0,
+            mode,
+            _padding: 0,
+        }
+    }
+}
+
+// Layout of a filter kernel, given explicitly as layouts derived from the shader expect a
+// filterable input texture, which Rgba32Float isn't without the FLOAT32_FILTERABLE feature
+fn layout_entries(params: bool, weights: bool) -> Vec<BindGroupLayoutEntry> {
+    let mut entries: Vec<BindGroupLayoutEntry> = vec![
+        create_compute_bind_group_layout_entry(
+            0,
+            BindingType::Texture {
+                sample_type: TextureSampleType::Float { filterable: false },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            },
+        ),
+        create_compute_bind_group_layout_entry(
+            1,
+            BindingType::StorageTexture {
+                access: StorageTextureAccess::WriteOnly,
+                format: IMAGE_FORMAT,
+                view_dimension: TextureViewDimension::D2,
+            },
+        ),
+    ];
+    if params {
+        entries.push(create_compute_bind_group_layout_entry(
+            PARAMS_BINDING,
+            BindingType::Buffer {
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        ));
+    }
+    if weights {
+        entries.push(create_compute_bind_group_layout_entry(
+            WEIGHTS_BINDING,
+            BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: true },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+        ));
+    }
+    entries
+}
+
+// Compiled filter: its kernel, buffers, and output size (the input's when None)
+struct FilterPass {
+    kernel: ComputeKernel,
+    params: Option<Buffer>,
+    weights: Option<Buffer>,
+    output_size: Option<(u32, u32)>,
+}
+
+pub struct FilterChainBuilder<'a> {
+    label: Option<&'a str>,
+    filters: Vec<Filter>,
+}
+
+impl<'a> FilterChainBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            label: None,
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    // Filters are applied in the order they are added
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filters.push(filter);
+        self
+    }
+
+    #[track_caller]
+    pub fn build(self, device: &Device) -> Result<FilterChain, FrameworkError> {
+        let label: &str = self.label.unwrap_or("filter_chain");
+        let built_in_source: String = format!(
+            "{FILTER_BINDINGS_WGSL}\n{}",
+            include_str!("shaders/image_filters.wgsl")
+        );
+        let built_in = |entry_point: &str, params: bool, weights: bool| {
+            ComputeKernel::builder(&built_in_source, entry_point)
+                .with_label(entry_point)
+                .with_layout_entries(&layout_entries(params, weights))
+                .build(device)
+        };
+        let params_buffer = |params: &[u8]| {
+            create_buffer(device, params, BufferUsages::UNIFORM, Some(label)).map(Some)
+        };
+
+        let mut passes: Vec<FilterPass> = Vec::new();
+        for filter in self.filters {
+            match filter {
+                Filter::Convolution {
+                    weights,
+                    width,
+                    height,
+                } => {
+                    if weights.is_empty() || weights.len() != width as usize * height as usize {
+                        return Err(FrameworkError::DimensionMismatch(format!(
+                            "{width}x{height} convolution kernel of {} weights",
+                            weights.len()
+                        )));
+                    }
+                    passes.push(FilterPass {
+                        kernel: built_in("convolve", true, true)?,
+                        params: params_buffer(bytemuck::bytes_of(&Params::kernel(width, height)))?,
+                        weights: Some(create_buffer(
+                            device,
+                            bytemuck::cast_slice(&weights),
+                            BufferUsages::STORAGE,
+                            Some(label),
+                        )?),
+                        output_size: None,
+                    });
+                }
+                Filter::GaussianBlur { sigma } => {
+                    let weights: Vec<f32> = gaussian_weights(sigma);
+                    let size: u32 = weights.len() as u32;
+                    for (kernel_width, kernel_height) in [(size, 1), (1, size)] {
+                        passes.push(FilterPass {
+                            kernel: built_in("convolve", true, true)?,
+                            params: params_buffer(bytemuck::bytes_of(&Params::kernel(
+                                kernel_width,
+                                kernel_height,
+                            )))?,
+                            weights: Some(create_buffer(
+                                device,
+                                bytemuck::cast_slice(&weights),
+                                BufferUsages::STORAGE,
+                                Some(label),
+                            )?),
+                            output_size: None,
+                        });
+                    }
+                }
+                Filter::Sobel => passes.push(FilterPass {
+                    kernel: built_in("sobel", false, false)?,
+                    params: None,
+                    weights: None,
+                    output_size: None,
+                }),
+                Filter::ColorConversion(conversion) => passes.push(FilterPass {
+                    kernel: built_in("convert_color", true, false)?,
+                    params: params_buffer(bytemuck::bytes_of(&Params::mode(conversion.mode())))?,
+                    weights: None,
+                    output_size: None,
+                }),
+                Filter::Resize {
+                    width,
+                    height,
+                    filter,
+                } => {
+                    if width == 0 || height == 0 {
+                        return Err(FrameworkError::DimensionMismatch(format!(
+                            "Resize to {width}x{height}"
+                        )));
+                    }
+                    let entry_point: &str = match filter {
+                        ResizeFilter::Nearest => "resize_nearest",
+                        ResizeFilter::Bilinear => "resize_bilinear",
+                    };
+                    passes.push(FilterPass {
+                        kernel: built_in(entry_point, false, false)?,
+                        params: None,
+                        weights: None,
+                        output_size: Some((width, height)),
+                    });
+                }
+                Filter::Custom(CustomFilter {
+                    source,
+                    entry_point,
+                    params,
+                    output_size,
+                }) => {
+                    let source: String = format!("{FILTER_BINDINGS_WGSL}\n{source}");
+                    passes.push(FilterPass {
+                        kernel: ComputeKernel::builder(&source, &entry_point)
+                            .with_label(&entry_point)
+                            .with_layout_entries(&layout_entries(params.is_some(), false))
+                            .build(device)?,
+                        params: match params {
+                            Some(params) => params_buffer(&params)?,
+                            None => None,
+                        },
+                        weights: None,
+                        output_size,
+                    });
+                }
+            }
+        }
+        Ok(FilterChain {
+            label: label.to_owned(),
+            passes,
+        })
+    }
+}
+
+impl Default for FilterChainBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Filters applied one after the other, each writing a new image read by the next one
+// -> Kernels and their buffers are created once by build(), intermediate images on every
+//    application
+// -> A Gaussian blur runs as two passes
+pub struct FilterChain {
+    label: String,
+    passes: Vec<FilterPass>,
+}
+
+impl FilterChain {
+    pub fn builder<'a>() -> FilterChainBuilder<'a> {
+        FilterChainBuilder::new()
+    }
+
+    // Number of kernel dispatches
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    // Size of the result for an input of this size
+    pub fn output_size(&self, width: u32, height: u32) -> (u32, u32) {
+        self.passes.iter().fold((width, height), |size, pass| {
+            pass.output_size.unwrap_or(size)
+        })
+    }
+
+    // <---- Application ---->
+    #[track_caller]
+    pub fn apply(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        input: &GpuImage,
+    ) -> Result<GpuImage, FrameworkError> {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("filter_chain_encoder"),
+            });
+        let output: GpuImage = self.record(device, &mut encoder, input)?;
+        queue.submit(Some(encoder.finish()));
+        Ok(output)
+    }
+
+    // The returned image holds the result once the encoder is submitted, the input is left
+    // unchanged (an empty chain copies it)
+    #[track_caller]
+    pub fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &GpuImage,
+    ) -> Result<GpuImage, FrameworkError> {
+        let label: Option<&str> = Some(&self.label);
+        if self.passes.is_empty() {
+            let output: GpuImage = GpuImage::empty(device, input.width(), input.height(), label)?;
+            encoder.copy_texture_to_texture(
+                texture_copy(input),
+                texture_copy(&output),
+                input.texture().size(),
+            );
+            return Ok(output);
+        }
+
+        let mut result: Option<GpuImage> = None;
+        for pass in self.passes.iter() {
+            let source: &GpuImage = result.as_ref().unwrap_or(input);
+            let (width, height): (u32, u32) = pass
+                .output_size
+                .unwrap_or((source.width(), source.height()));
+            let target: GpuImage = GpuImage::empty(device, width, height, label)?;
+
+            let mut entries: Vec<BindGroupEntry> = vec![
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(source.view()),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::TextureView(target.view()),
+                },
+            ];
+            for (binding, buffer) in [
+                (PARAMS_BINDING, &pass.params),
+                (WEIGHTS_BINDING, &pass.weights),
+            ] {
+                if let Some(buffer) = buffer {
+                    entries.push(BindGroupEntry {
+                        binding,
+                        resource: buffer.as_entire_binding(),
+                    });
+                }
+            }
+            let bind_group: BindGroup = create_bind_group_with_entries(
+                device,
+                &entries,
+                pass.kernel.bind_group_layout(),
+                pass.kernel.label(),
+            )?;
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                    label: pass.kernel.label(),
+                    timestamp_writes: None,
+                });
+                pass.kernel.dispatch_with(
+                    &mut compute_pass,
+                    &bind_group,
+                    pass.kernel.workgroups_for_grid(width, height),
+                );
+            }
+            result = Some(target);
+        }
+        Ok(result.expect("Chain has at least one pass"))
+    }
+}
+
+fn texture_copy(image: &GpuImage) -> ImageCopyTexture<'_> {
+    ImageCopyTexture {
+        texture: image.texture(),
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::gpu_context::GPUContext,
+        image_processing::{
+            filter::{ColorConversion, Filter, ResizeFilter},
+            gpu_image::GpuImage,
+            reference::{cpu_filter_chain, CpuImage},
+        },
+        verification::{
+            compare::{compare, Tolerance},
+            kernel_check::uniform_f32,
+            verification_context,
+        },
+    };
+
+    use super::FilterChain;
+
+    // Single precision on both sides, only the order of operations differs
+    const TOLERANCE: f64 = 1e-5;
+
+    fn random_image(seed: u64, width: u32, height: u32) -> CpuImage {
+        let values: Vec<f32> = uniform_f32(seed, (width * height * 4) as usize, 0.0..1.0);
+        CpuImage::new(width, height, bytemuck::cast_slice(&values).to_vec())
+    }
+
+    fn apply(
+        context: &GPUContext,
+        image: &CpuImage,
+        filters: &[Filter],
+    ) -> Result<CpuImage, FrameworkError> {
+        let (device, queue) = (&context.device, &context.queue);
+        let chain: FilterChain = filters
+            .iter()
+            .cloned()
+            .fold(FilterChain::builder(), |builder, filter| {
+                builder.with_filter(filter)
+            })
+            .build(device)?;
+        let input: GpuImage = GpuImage::from_pixels(
+            device,
+            queue,
+            image.width,
+            image.height,
+            &image.pixels,
+            Some("input"),
+        )?;
+        let output: GpuImage = chain.apply(device, queue, &input)?;
+        Ok(CpuImage::new(
+            output.width(),
+            output.height(),
+            output.read(device, queue)?,
+        ))
+    }
+
+    fn check_chain(
+        context: &GPUContext,
+        image: &CpuImage,
+        filters: &[Filter],
+    ) -> Result<(), FrameworkError> {
+        let actual: CpuImage = apply(context, image, filters)?;
+        let expected: CpuImage = cpu_filter_chain(image, filters).expect("Built-in filters");
+        assert_eq!(
+            (actual.width, actual.height),
+            (expected.width, expected.height),
+            "{filters:?}"
+        );
+        compare(
+            &actual.pixels,
+            &expected.pixels,
+            Tolerance::new().with_absolute(TOLERANCE),
+        )
+        .into_result()
+    }
+
+    #[test]
+    fn convolution_clamps_to_edges() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let image: CpuImage = random_image(1, 7, 5);
+
+        // Shifts right by one, the first column repeats the edge
+        let shift: Filter = Filter::Convolution {
+            weights: vec![1.0, 0.0, 0.0],
+            width: 3,
+            height: 1,
+        };
+        let shifted: CpuImage = apply(&context, &image, std::slice::from_ref(&shift))?;
+        for y in 0..5 {
+            let row: &[[f32; 4]] = &image.pixels[y * 7..(y + 1) * 7];
+            let shifted_row: &[[f32; 4]] = &shifted.pixels[y * 7..(y + 1) * 7];
+            assert_eq!(shifted_row[0], row[0]);
+            assert_eq!(shifted_row[1..], row[..6]);
+        }
+        check_chain(&context, &image, &[shift])?;
+
+        // Asymmetric kernel wider than the image, reaching past every edge
+        let weights: Vec<f32> = uniform_f32(2, 9 * 3, -1.0..1.0);
+        check_chain(
+            &context,
+            &image,
+            &[Filter::Convolution {
+                weights,
+                width: 9,
+                height: 3,
+            }],
+        )?;
+        check_chain(&context, &image, &[Filter::sharpen()])
+    }
+
+    #[test]
+    fn blurs_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let image: CpuImage = random_image(3, 13, 9);
+        check_chain(&context, &image, &[Filter::box_blur(1)])?;
+        check_chain(&context, &image, &[Filter::box_blur(3)])?;
+        check_chain(&context, &image, &[Filter::GaussianBlur { sigma: 0.0 }])?;
+        check_chain(&context, &image, &[Filter::GaussianBlur { sigma: 1.5 }])
+    }
+
+    #[test]
+    fn sobel_matches_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let image: CpuImage = random_image(4, 11, 6);
+        check_chain(&context, &image, &[Filter::Sobel])?;
+
+        // Flat images have no gradient, even at the edges
+        let flat: CpuImage = CpuImage::new(4, 3, vec![[0.3, 0.6, 0.9, 0.5]; 12]);
+        let gradient: CpuImage = apply(&context, &flat, &[Filter::Sobel])?;
+        assert!(gradient
+            .pixels
+            .iter()
+            .all(|&pixel| pixel == [0.0, 0.0, 0.0, 0.5]));
+        Ok(())
+    }
+
+    #[test]
+    fn color_conversions_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let image: CpuImage = random_image(5, 8, 8);
+        let conversion = |conversion: ColorConversion| Filter::ColorConversion(conversion);
+        check_chain(
+            &context,
+            &image,
+            &[conversion(ColorConversion::RgbToGrayscale)],
+        )?;
+        for (forward, backward) in [
+            (ColorConversion::RgbToHsv, ColorConversion::HsvToRgb),
+            (ColorConversion::RgbToYCbCr, ColorConversion::YCbCrToRgb),
+            (ColorConversion::SrgbToLinear, ColorConversion::LinearToSrgb),
+        ] {
+            check_chain(&context, &image, &[conversion(forward)])?;
+            check_chain(&context, &image, &[conversion(backward)])?;
+
+            // Round trips give back the input, up to the rounding of the YCbCr coefficients
+            let round_trip: CpuImage = apply(
+                &context,
+                &image,
+                &[conversion(forward), conversion(backward)],
+            )?;
+            compare(
+                &round_trip.pixels,
+                &image.pixels,
+                Tolerance::new().with_absolute(2e-3),
+            )
+            .into_result()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn resizes_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let image: CpuImage = random_image(6, 10, 6);
+        for filter in [ResizeFilter::Nearest, ResizeFilter::Bilinear] {
+            for (width, height) in [(10, 6), (23, 13), (4, 3), (7, 9), (1, 1)] {
+                check_chain(
+                    &context,
+                    &image,
+                    &[Filter::Resize {
+                        width,
+                        height,
+                        filter,
+                    }],
+                )?;
+            }
+        }
+
+        // Doubling with nearest repeats every pixel twice
+        let doubled: CpuImage = apply(
+            &context,
+            &image,
+            &[Filter::Resize {
+                width: 20,
+                height: 12,
+                filter: ResizeFilter::Nearest,
+            }],
+        )?;
+        assert_eq!(doubled.pixels[2 * 20 + 5], image.pixels[10 + 2]);
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+use std::{mem::size_of, path::Path};
+
+use image::{DynamicImage, ImageFormat, Rgba32FImage};
+use wgpu::{
+    Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, Texture, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureUsages, TextureView,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{readback::Readback, validation::validate},
+};
+
+// Format of every image texture, so filters can be chained in any order
+pub const IMAGE_FORMAT: TextureFormat = TextureFormat::Rgba32Float;
+
+// RGBA image in a texture usable as a filter input and output
+// -> Channels hold the values stored in the file scaled to [0, 1], without colour space
+//    conversion (use ColorConversion::SrgbToLinear to filter in linear light)
+// -> Values outside [0, 1] are kept between filters and clamped when saving
+pub struct GpuImage {
+    texture: Texture,
+    view: TextureView,
+}
+
+impl GpuImage {
+    #[track_caller]
+    pub fn empty(
+        device: &Device,
+        width: u32,
+        height: u32,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        let texture: Texture = validate(device, label, || {
+            device.create_texture(&TextureDescriptor {
+                label,
+                size: Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: IMAGE_FORMAT,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::STORAGE_BINDING
+                    | TextureUsages::COPY_SRC
+                    | TextureUsages::COPY_DST,
+                view_formats: &[],
+            })
+        })?;
+        let view: TextureView = texture.create_view(&Default::default());
+        Ok(Self { texture, view })
+    }
+
+    // Row-major pixels, width * height of them
+    #[track_caller]
+    pub fn from_pixels(
+        device: &Device,
+        queue: &Queue,
+        width: u32,
+        height: u32,
+        pixels: &[[f32; 4]],
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        if pixels.len() != width as usize * height as usize {
+            return Err(FrameworkError::DimensionMismatch(format!(
+                "{width}x{height} image from {} pixels",
+                pixels.len()
+            )));
+        }
+        let image: Self = Self::empty(device, width, height, label)?;
+        queue.write_texture(
+            ImageCopyTexture {
+                texture: &image.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::All,
+            },
+            bytemuck::cast_slice(pixels),
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width * size_of::<[f32; 4]>() as u32),
+                rows_per_image: Some(height),
+            },
+            image.texture.size(),
+        );
+        Ok(image)
+    }
+
+    // PNG or JPEG file, converted to RGBA
+    #[track_caller]
+    pub fn load(
+        device: &Device,
+        queue: &Queue,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, FrameworkError> {
+        let path: &Path = path.as_ref();
+        let image: Rgba32FImage = image::open(path)?.into_rgba32f();
+        let label: String = path.display().to_string();
+        Self::from_pixels(
+            device,
+            queue,
+            image.width(),
+            image.height(),
+            bytemuck::cast_slice(image.as_raw()),
+            Some(&label),
+        )
+    }
+
+    // <---- Accessors ---->
+    pub fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.height()
+    }
+
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    // <---- Reading ---->
+    // Row-major pixels, blocks until they are available
+    #[track_caller]
+    pub fn read(&self, device: &Device, queue: &Queue) -> Result<Vec<[f32; 4]>, FrameworkError> {
+        Readback::texture(device, queue, &self.texture)?.wait(device)
+    }
+
+    // Format given by the extension, 8 bits per channel, JPEG files drop the alpha channel
+    #[track_caller]
+    pub fn save(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        path: impl AsRef<Path>,
+    ) -> Result<(), FrameworkError> {
+        let path: &Path = path.as_ref();
+        let pixels: Vec<f32> = bytemuck::cast_vec(self.read(device, queue)?);
+        let image: Rgba32FImage = Rgba32FImage::from_raw(self.width(), self.height(), pixels)
+            .expect("Readback holds every pixel of the image");
+        // Conversion to 8 bits clamps to [0, 1]
+        let image: DynamicImage = DynamicImage::ImageRgba32F(image);
+        match ImageFormat::from_path(path)? {
+            ImageFormat::Jpeg => image.into_rgb8().save(path)?,
+            _ => image.into_rgba8().save(path)?,
+        }
+        Ok(())
+    }
+}
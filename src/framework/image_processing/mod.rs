@@ -0,0 +1,4 @@
+pub mod filter;
+pub mod filter_chain;
+pub mod gpu_image;
+pub mod reference;
@@ -0,0 +1,187 @@
+use super::filter::{gaussian_weights, ColorConversion, Filter, ResizeFilter};
+
+// Row-major RGBA pixels on the CPU
+#[derive(Clone, Debug, PartialEq)]
+pub struct CpuImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[f32; 4]>,
+}
+
+impl CpuImage {
+    pub fn new(width: u32, height: u32, pixels: Vec<[f32; 4]>) -> Self {
+        debug_assert_eq!(pixels.len(), width as usize * height as usize);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    // Pixels outside the image repeat the closest edge pixel
+    fn get_clamped(&self, x: i64, y: i64) -> [f32; 4] {
+        let x: usize = x.clamp(0, self.width as i64 - 1) as usize;
+        let y: usize = y.clamp(0, self.height as i64 - 1) as usize;
+        self.pixels[y * self.width as usize + x]
+    }
+
+    fn map_pixels(&self, width: u32, height: u32, pixel: impl Fn(i64, i64) -> [f32; 4]) -> Self {
+        let pixels: Vec<[f32; 4]> = (0..height as i64)
+            .flat_map(|y| (0..width as i64).map(move |x| (x, y)))
+            .map(|(x, y)| pixel(x, y))
+            .collect();
+        Self::new(width, height, pixels)
+    }
+}
+
+// Reference implementations of the built-in filters, None for custom filters
+pub fn cpu_filter(image: &CpuImage, filter: &Filter) -> Option<CpuImage> {
+    match filter {
+        Filter::Convolution {
+            weights,
+            width,
+            height,
+        } => Some(convolve(image, weights, *width, *height)),
+        Filter::GaussianBlur { sigma } => {
+            let weights: Vec<f32> = gaussian_weights(*sigma);
+            let size: u32 = weights.len() as u32;
+            let rows: CpuImage = convolve(image, &weights, size, 1);
+            Some(convolve(&rows, &weights, 1, size))
+        }
+        Filter::Sobel => Some(sobel(image)),
+        Filter::ColorConversion(conversion) => {
+            Some(image.map_pixels(image.width, image.height, |x, y| {
+                let [r, g, b, a] = image.get_clamped(x, y);
+                let [r, g, b] = convert_color([r, g, b], *conversion);
+                [r, g, b, a]
+            }))
+        }
+        Filter::Resize {
+            width,
+            height,
+            filter,
+        } => Some(resize(image, *width, *height, *filter)),
+        Filter::Custom(_) => None,
+    }
+}
+
+// Applies every filter in order, None if one of them is a custom filter
+pub fn cpu_filter_chain(image: &CpuImage, filters: &[Filter]) -> Option<CpuImage> {
+    filters
+        .iter()
+        .try_fold(image.clone(), |image, filter| cpu_filter(&image, filter))
+}
+
+fn convolve(image: &CpuImage, weights: &[f32], width: u32, height: u32) -> CpuImage {
+    let (center_x, center_y): (i64, i64) = ((width / 2) as i64, (height / 2) as i64);
+    image.map_pixels(image.width, image.height, |x, y| {
+        let mut sum: [f32; 4] = [0.0; 4];
+        for ky in 0..height as i64 {
+            for kx in 0..width as i64 {
+                let weight: f32 = weights[(ky * width as i64 + kx) as usize];
+                let pixel: [f32; 4] = image.get_clamped(x + kx - center_x, y + ky - center_y);
+                for channel in 0..4 {
+                    sum[channel] += weight * pixel[channel];
+                }
+            }
+        }
+        sum
+    })
+}
+
+fn luma([r, g, b, _]: [f32; 4]) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+fn sobel(image: &CpuImage) -> CpuImage {
+    image.map_pixels(image.width, image.height, |x, y| {
+        let at = |dx: i64, dy: i64| luma(image.get_clamped(x + dx, y + dy));
+        let gx: f32 =
+            (at(1, -1) + 2.0 * at(1, 0) + at(1, 1)) - (at(-1, -1) + 2.0 * at(-1, 0) + at(-1, 1));
+        let gy: f32 =
+            (at(-1, 1) + 2.0 * at(0, 1) + at(1, 1)) - (at(-1, -1) + 2.0 * at(0, -1) + at(1, -1));
+        let magnitude: f32 = (gx * gx + gy * gy).sqrt();
+        [magnitude, magnitude, magnitude, image.get_clamped(x, y)[3]]
+    })
+}
+
+pub fn convert_color([r, g, b]: [f32; 3], conversion: ColorConversion) -> [f32; 3] {
+    match conversion {
+        ColorConversion::RgbToGrayscale => [luma([r, g, b, 0.0]); 3],
+        ColorConversion::RgbToHsv => {
+            let max: f32 = r.max(g).max(b);
+            let delta: f32 = max - r.min(g).min(b);
+            let hue: f32 = match delta > 0.0 {
+                false => 0.0,
+                true => {
+                    let sector: f32 = if max == r {
+                        (g - b) / delta
+                    } else if max == g {
+                        (b - r) / delta + 2.0
+                    } else {
+                        (r - g) / delta + 4.0
+                    };
+                    (sector / 6.0).rem_euclid(1.0)
+                }
+            };
+            let saturation: f32 = if max > 0.0 { delta / max } else { 0.0 };
+            [hue, saturation, max]
+        }
+        ColorConversion::HsvToRgb => {
+            let [hue, saturation, value] = [r, g, b];
+            [1.0, 2.0 / 3.0, 1.0 / 3.0].map(|offset: f32| {
+                let k: f32 = (hue + offset).rem_euclid(1.0) * 6.0;
+                let channel: f32 = ((k - 3.0).abs() - 1.0).clamp(0.0, 1.0);
+                value * (1.0 + (channel - 1.0) * saturation)
+            })
+        }
+        ColorConversion::RgbToYCbCr => {
+            let y: f32 = 0.299 * r + 0.587 * g + 0.114 * b;
+            [y, 0.5 + (b - y) * 0.564, 0.5 + (r - y) * 0.713]
+        }
+        ColorConversion::YCbCrToRgb => {
+            let (y, cb, cr): (f32, f32, f32) = (r, g - 0.5, b - 0.5);
+            [y + 1.403 * cr, y - 0.344 * cb - 0.714 * cr, y + 1.773 * cb]
+        }
+        ColorConversion::SrgbToLinear => [r, g, b].map(|c| match c <= 0.04045 {
+            true => c / 12.92,
+            false => ((c + 0.055) / 1.055).powf(2.4),
+        }),
+        ColorConversion::LinearToSrgb => [r, g, b].map(|c| match c <= 0.0031308 {
+            true => c * 12.92,
+            false => 1.055 * c.powf(1.0 / 2.4) - 0.055,
+        }),
+    }
+}
+
+fn resize(image: &CpuImage, width: u32, height: u32, filter: ResizeFilter) -> CpuImage {
+    let scale_x: f32 = image.width as f32 / width as f32;
+    let scale_y: f32 = image.height as f32 / height as f32;
+    image.map_pixels(width, height, |x, y| {
+        // Position in the input of the centre of the output pixel
+        let source_x: f32 = (x as f32 + 0.5) * scale_x;
+        let source_y: f32 = (y as f32 + 0.5) * scale_y;
+        match filter {
+            ResizeFilter::Nearest => {
+                image.get_clamped(source_x.floor() as i64, source_y.floor() as i64)
+            }
+            ResizeFilter::Bilinear => {
+                let (base_x, base_y): (f32, f32) =
+                    ((source_x - 0.5).floor(), (source_y - 0.5).floor());
+                let (tx, ty): (f32, f32) = (source_x - 0.5 - base_x, source_y - 0.5 - base_y);
+                let (x0, y0): (i64, i64) = (base_x as i64, base_y as i64);
+                let mix = |a: [f32; 4], b: [f32; 4], t: f32| {
+                    [0, 1, 2, 3].map(|channel| a[channel] + (b[channel] - a[channel]) * t)
+                };
+                let top: [f32; 4] =
+                    mix(image.get_clamped(x0, y0), image.get_clamped(x0 + 1, y0), tx);
+                let bottom: [f32; 4] = mix(
+                    image.get_clamped(x0, y0 + 1),
+                    image.get_clamped(x0 + 1, y0 + 1),
+                    tx,
+                );
+                mix(top, bottom, ty)
+            }
+        }
+    })
+}
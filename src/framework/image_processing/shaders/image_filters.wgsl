@@ -0,0 +1,181 @@
+// Built-in image filters, one invocation per output pixel
+// -> Bindings 0 and 1 are declared by FILTER_BINDINGS_WGSL, prepended to this source
+
+struct FilterParams {
+    kernel_width: u32,
+    kernel_height: u32,
+    mode: u32,
+    _padding: u32,
+}
+
+@group(0) @binding(2) var<uniform> params: FilterParams;
+@group(0) @binding(3) var<storage, read> weights: array<f32>;
+
+// Must match ColorConversion::mode()
+const RGB_TO_GRAYSCALE: u32 = 0u;
+const RGB_TO_HSV: u32 = 1u;
+const HSV_TO_RGB: u32 = 2u;
+const RGB_TO_YCBCR: u32 = 3u;
+const YCBCR_TO_RGB: u32 = 4u;
+const SRGB_TO_LINEAR: u32 = 5u;
+const LINEAR_TO_SRGB: u32 = 6u;
+
+// Pixels outside the image repeat the closest edge pixel
+fn load_clamped(position: vec2<i32>) -> vec4<f32> {
+    let size: vec2<i32> = vec2<i32>(textureDimensions(input_image));
+    return textureLoad(input_image, clamp(position, vec2<i32>(0), size - 1), 0);
+}
+
+fn is_outside(id: vec3<u32>) -> bool {
+    let size: vec2<u32> = textureDimensions(output_image);
+    return id.x >= size.x || id.y >= size.y;
+}
+
+// <---- Convolution ---->
+// Correlation with a row-major kernel centred on (kernel_width / 2, kernel_height / 2)
+@compute @workgroup_size(8, 8)
+fn convolve(@builtin(global_invocation_id) id: vec3<u32>) {
+    if is_outside(id) {
+        return;
+    }
+    let center: vec2<i32> = vec2<i32>(i32(params.kernel_width / 2u), i32(params.kernel_height / 2u));
+    var sum: vec4<f32> = vec4<f32>(0.0);
+    for (var ky: u32 = 0u; ky < params.kernel_height; ky++) {
+        for (var kx: u32 = 0u; kx < params.kernel_width; kx++) {
+            let offset: vec2<i32> = vec2<i32>(i32(kx), i32(ky)) - center;
+            let weight: f32 = weights[ky * params.kernel_width + kx];
+            sum += weight * load_clamped(vec2<i32>(id.xy) + offset);
+        }
+    }
+    textureStore(output_image, id.xy, sum);
+}
+
+// <---- Edges ---->
+fn luma(color: vec4<f32>) -> f32 {
+    return dot(color.rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+// Gradient magnitude of the luma in every colour channel, alpha is kept
+@compute @workgroup_size(8, 8)
+fn sobel(@builtin(global_invocation_id) id: vec3<u32>) {
+    if is_outside(id) {
+        return;
+    }
+    let p: vec2<i32> = vec2<i32>(id.xy);
+    let top_left: f32 = luma(load_clamped(p + vec2<i32>(-1, -1)));
+    let top: f32 = luma(load_clamped(p + vec2<i32>(0, -1)));
+    let top_right: f32 = luma(load_clamped(p + vec2<i32>(1, -1)));
+    let left: f32 = luma(load_clamped(p + vec2<i32>(-1, 0)));
+    let right: f32 = luma(load_clamped(p + vec2<i32>(1, 0)));
+    let bottom_left: f32 = luma(load_clamped(p + vec2<i32>(-1, 1)));
+    let bottom: f32 = luma(load_clamped(p + vec2<i32>(0, 1)));
+    let bottom_right: f32 = luma(load_clamped(p + vec2<i32>(1, 1)));
+    let gx: f32 = (top_right + 2.0 * right + bottom_right) - (top_left + 2.0 * left + bottom_left);
+    let gy: f32 = (bottom_left + 2.0 * bottom + bottom_right) - (top_left + 2.0 * top + top_right);
+    let magnitude: f32 = sqrt(gx * gx + gy * gy);
+    textureStore(output_image, id.xy, vec4<f32>(vec3<f32>(magnitude), load_clamped(p).a));
+}
+
+// <---- Colour conversion ---->
+fn rgb_to_hsv(c: vec3<f32>) -> vec3<f32> {
+    let max_c: f32 = max(c.r, max(c.g, c.b));
+    let min_c: f32 = min(c.r, min(c.g, c.b));
+    let delta: f32 = max_c - min_c;
+    var hue: f32 = 0.0;
+    if delta > 0.0 {
+        if max_c == c.r {
+            hue = (c.g - c.b) / delta;
+        } else if max_c == c.g {
+            hue = (c.b - c.r) / delta + 2.0;
+        } else {
+            hue = (c.r - c.g) / delta + 4.0;
+        }
+        hue = fract(hue / 6.0);
+    }
+    var saturation: f32 = 0.0;
+    if max_c > 0.0 {
+        saturation = delta / max_c;
+    }
+    return vec3<f32>(hue, saturation, max_c);
+}
+
+fn hsv_to_rgb(c: vec3<f32>) -> vec3<f32> {
+    let k: vec3<f32> = fract(vec3<f32>(c.x) + vec3<f32>(1.0, 2.0 / 3.0, 1.0 / 3.0)) * 6.0;
+    return c.z * mix(vec3<f32>(1.0), clamp(abs(k - 3.0) - 1.0, vec3<f32>(0.0), vec3<f32>(1.0)), c.y);
+}
+
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    return select(pow((c + 0.055) / 1.055, vec3<f32>(2.4)), c / 12.92, c <= vec3<f32>(0.04045));
+}
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    return select(1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055, c * 12.92, c <= vec3<f32>(0.0031308));
+}
+
+@compute @workgroup_size(8, 8)
+fn convert_color(@builtin(global_invocation_id) id: vec3<u32>) {
+    if is_outside(id) {
+        return;
+    }
+    let color: vec4<f32> = textureLoad(input_image, vec2<i32>(id.xy), 0);
+    var rgb: vec3<f32> = color.rgb;
+    switch params.mode {
+        case RGB_TO_GRAYSCALE: {
+            rgb = vec3<f32>(luma(color));
+        }
+        case RGB_TO_HSV: {
+            rgb = rgb_to_hsv(rgb);
+        }
+        case HSV_TO_RGB: {
+            rgb = hsv_to_rgb(rgb);
+        }
+        // BT.601 full range, chroma offset by 0.5
+        case RGB_TO_YCBCR: {
+            let y: f32 = dot(rgb, vec3<f32>(0.299, 0.587, 0.114));
+            rgb = vec3<f32>(y, 0.5 + (rgb.b - y) * 0.564, 0.5 + (rgb.r - y) * 0.713);
+        }
+        case YCBCR_TO_RGB: {
+            let y: f32 = rgb.x;
+            let cb: f32 = rgb.y - 0.5;
+            let cr: f32 = rgb.z - 0.5;
+            rgb = vec3<f32>(y + 1.403 * cr, y - 0.344 * cb - 0.714 * cr, y + 1.773 * cb);
+        }
+        case SRGB_TO_LINEAR: {
+            rgb = srgb_to_linear(rgb);
+        }
+        case LINEAR_TO_SRGB: {
+            rgb = linear_to_srgb(rgb);
+        }
+        default: {}
+    }
+    textureStore(output_image, id.xy, vec4<f32>(rgb, color.a));
+}
+
+// <---- Resizing ---->
+// Position in the input of the centre of an output pixel
+fn source_position(id: vec3<u32>) -> vec2<f32> {
+    let scale: vec2<f32> = vec2<f32>(textureDimensions(input_image)) / vec2<f32>(textureDimensions(output_image));
+    return (vec2<f32>(id.xy) + 0.5) * scale;
+}
+
+@compute @workgroup_size(8, 8)
+fn resize_nearest(@builtin(global_invocation_id) id: vec3<u32>) {
+    if is_outside(id) {
+        return;
+    }
+    textureStore(output_image, id.xy, load_clamped(vec2<i32>(floor(source_position(id)))));
+}
+
+@compute @workgroup_size(8, 8)
+fn resize_bilinear(@builtin(global_invocation_id) id: vec3<u32>) {
+    if is_outside(id) {
+        return;
+    }
+    let position: vec2<f32> = source_position(id) - 0.5;
+    let base: vec2<f32> = floor(position);
+    let t: vec2<f32> = position - base;
+    let p: vec2<i32> = vec2<i32>(base);
+    let top: vec4<f32> = mix(load_clamped(p), load_clamped(p + vec2<i32>(1, 0)), t.x);
+    let bottom: vec4<f32> = mix(load_clamped(p + vec2<i32>(0, 1)), load_clamped(p + vec2<i32>(1, 1)), t.x);
+    textureStore(output_image, id.xy, mix(top, bottom, t.y));
+}
@@ -0,0 +1,138 @@
+use wgpu::{Buffer, BufferUsages, Device, Queue};
+
+use crate::framework::{error::FrameworkError, gpu::gpu_buffer::GpuBuffer};
+
+use super::matrix::{Layout, Matrix};
+
+// Dense f32 matrix in a storage buffer
+pub struct GpuMatrix {
+    data: GpuBuffer<f32>,
+    rows: u32,
+    columns: u32,
+    layout: Layout,
+}
+
+impl GpuMatrix {
+    #[track_caller]
+    pub fn from_matrix(
+        device: &Device,
+        matrix: &Matrix,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            data: GpuBuffer::storage(device, &matrix.data, label)?,
+            rows: matrix.rows,
+            columns: matrix.columns,
+            layout: matrix.layout,
+        })
+    }
+
+    #[track_caller]
+    pub fn zeroed(
+        device: &Device,
+        rows: u32,
+        columns: u32,
+        layout: Layout,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            data: GpuBuffer::zeroed(
+                device,
+                (rows * columns) as usize,
+                BufferUsages::STORAGE,
+                label,
+            )?,
+            rows,
+            columns,
+            layout,
+        })
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+
+    // Distance between consecutive rows and consecutive columns
+    pub fn strides(&self) -> (u32, u32) {
+        self.layout.strides(self.rows, self.columns)
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        self.data.buffer()
+    }
+
+    // Blocks until the contents are available
+    #[track_caller]
+    pub fn read(&self, device: &Device, queue: &Queue) -> Result<Matrix, FrameworkError> {
+        Ok(Matrix {
+            rows: self.rows,
+            columns: self.columns,
+            layout: self.layout,
+            data: self.data.read(device, queue)?,
+        })
+    }
+}
+
+// Dense f32 vector in a storage buffer
+pub struct GpuVector {
+    data: GpuBuffer<f32>,
+}
+
+impl GpuVector {
+    #[track_caller]
+    pub fn from_slice(
+        device: &Device,
+        values: &[f32],
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            data: GpuBuffer::storage(device, values, label)?,
+        })
+    }
+
+    #[track_caller]
+    pub fn zeroed(
+        device: &Device,
+        len: usize,
+        label: Option<&str>,
+    ) -> Result<Self, FrameworkError> {
+        Ok(Self {
+            data: GpuBuffer::zeroed(device, len, BufferUsages::STORAGE, label)?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        self.data.buffer()
+    }
+
+    #[track_caller]
+    pub fn write(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        values: &[f32],
+    ) -> Result<(), FrameworkError> {
+        self.data.write(device, queue, values)
+    }
+
+    #[track_caller]
+    pub fn read(&self, device: &Device, queue: &Queue) -> Result<Vec<f32>, FrameworkError> {
+        self.data.read(device, queue)
+    }
+}
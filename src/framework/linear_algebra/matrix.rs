@@ -0,0 +1,138 @@
+use glam::Mat4;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    #[default]
+    RowMajor,
+    ColumnMajor,
+}
+
+impl Layout {
+    // Distance between consecutive rows and consecutive columns of a rows x columns matrix
+    pub fn strides(self, rows: u32, columns: u32) -> (u32, u32) {
+        match self {
+            Self::RowMajor => (columns, 1),
+            Self::ColumnMajor => (1, rows),
+        }
+    }
+
+    pub fn index(self, row: u32, column: u32, rows: u32, columns: u32) -> usize {
+        let (row_stride, column_stride) = self.strides(rows, columns);
+        (row * row_stride + column * column_stride) as usize
+    }
+}
+
+// Dense f32 matrix on the CPU, used to fill and read back GPU matrices
+// and as the reference implementation of the GPU kernels
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix {
+    pub rows: u32,
+    pub columns: u32,
+    pub layout: Layout,
+    pub data: Vec<f32>,
+}
+
+impl Matrix {
+    // Returns None if the data doesn't hold rows x columns elements
+    pub fn new(rows: u32, columns: u32, layout: Layout, data: Vec<f32>) -> Option<Self> {
+        (data.len() == (rows * columns) as usize).then_some(Self {
+            rows,
+            columns,
+            layout,
+            data,
+        })
+    }
+
+    pub fn zeros(rows: u32, columns: u32, layout: Layout) -> Self {
+        Self {
+            rows,
+            columns,
+            layout,
+            data: vec![0.0; (rows * columns) as usize],
+        }
+    }
+
+    pub fn from_fn(
+        rows: u32,
+        columns: u32,
+        layout: Layout,
+        element: impl Fn(u32, u32) -> f32,
+    ) -> Self {
+        let mut matrix: Self = Self::zeros(rows, columns, layout);
+        for row in 0..rows {
+            for column in 0..columns {
+                matrix.set(row, column, element(row, column));
+            }
+        }
+        matrix
+    }
+
+    // glam matrices are column-major
+    pub fn from_mat4(matrix: Mat4) -> Self {
+        Self {
+            rows: 4,
+            columns: 4,
+            layout: Layout::ColumnMajor,
+            data: matrix.to_cols_array().to_vec(),
+        }
+    }
+
+    pub fn get(&self, row: u32, column: u32) -> f32 {
+        self.data[self.layout.index(row, column, self.rows, self.columns)]
+    }
+
+    pub fn set(&mut self, row: u32, column: u32, value: f32) {
+        let index: usize = self.layout.index(row, column, self.rows, self.columns);
+        self.data[index] = value;
+    }
+
+    // Same matrix stored in another layout
+    pub fn to_layout(&self, layout: Layout) -> Self {
+        Self::from_fn(self.rows, self.columns, layout, |row, column| {
+            self.get(row, column)
+        })
+    }
+
+    // <---- Reference implementations ---->
+    // None if the inner dimensions differ
+    pub fn matmul(&self, other: &Self, layout: Layout) -> Option<Self> {
+        (self.columns == other.rows).then(|| {
+            Self::from_fn(self.rows, other.columns, layout, |row, column| {
+                (0..self.columns)
+                    .map(|i| self.get(row, i) * other.get(i, column))
+                    .sum()
+            })
+        })
+    }
+
+    pub fn transpose(&self) -> Self {
+        Self::from_fn(self.columns, self.rows, self.layout, |row, column| {
+            self.get(column, row)
+        })
+    }
+
+    pub fn matvec(&self, vector: &[f32]) -> Option<Vec<f32>> {
+        (self.columns as usize == vector.len()).then(|| {
+            (0..self.rows)
+                .map(|row| {
+                    (0..self.columns)
+                        .map(|column| self.get(row, column) * vector[column as usize])
+                        .sum()
+                })
+                .collect()
+        })
+    }
+}
+
+// y = alpha * x + y
+pub fn cpu_axpy(alpha: f32, x: &[f32], y: &mut [f32]) {
+    y.iter_mut().zip(x).for_each(|(y, x)| *y += alpha * x);
+}
+
+pub fn cpu_dot(x: &[f32], y: &[f32]) -> f32 {
+    x.iter().zip(y).map(|(x, y)| x * y).sum()
+}
+
+pub fn cpu_norm(x: &[f32]) -> f32 {
+    cpu_dot(x, x).sqrt()
+}
@@ -0,0 +1,3 @@
+pub mod gpu_matrix;
+pub mod matrix;
+pub mod operations;
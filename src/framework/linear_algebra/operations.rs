@@ -0,0 +1,489 @@
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    BindGroup, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor,
+    ComputePassDescriptor, Device, Queue,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        compute_kernel::ComputeKernel,
+        readback::Readback,
+        utilities::{create_buffer, create_empty_buffer},
+    },
+    primitives::reduce::{ReduceOp, Reduction},
+};
+
+use super::{
+    gpu_matrix::{GpuMatrix, GpuVector},
+    matrix::Layout,
+};
+
+// Must match the shader
+const TILE_SIZE: u32 = 16;
+
+#[repr(C)]
+#[derive(Clone, Copy, Default, Pod, Zeroable)]
+struct Params {
+    rows: u32,
+    columns: u32,
+    inner: u32,
+    a_row_stride: u32,
+    a_column_stride: u32,
+    b_row_stride: u32,
+    b_column_stride: u32,
+    c_row_stride: u32,
+    c_column_stride: u32,
+    alpha: f32,
+    _padding: [u32; 2],
+}
+
+// Compiled linear algebra kernels, create once per device
+// -> Every operation is submitted immediately, results stay on the GPU until read
+pub struct LinearAlgebra {
+    matmul_kernel: ComputeKernel,
+    transpose_kernel: ComputeKernel,
+    matvec_kernel: ComputeKernel,
+    axpy_kernel: ComputeKernel,
+    multiply_kernel: ComputeKernel,
+    sum: Reduction<f32>,
+}
+
+impl LinearAlgebra {
+    #[track_caller]
+    pub fn new(device: &Device) -> Result<Self, FrameworkError> {
+        let source: &str = include_str!("shaders/linear_algebra.wgsl");
+        let kernel = |entry_point: &str| {
+            ComputeKernel::builder(source, entry_point)
+                .with_label(entry_point)
+                .build(device)
+        };
+        Ok(Self {
+            matmul_kernel: kernel("matmul")?,
+            transpose_kernel: kernel("transpose")?,
+            matvec_kernel: kernel("matvec")?,
+            axpy_kernel: kernel("axpy")?,
+            multiply_kernel: kernel("multiply")?,
+            sum: Reduction::new(device, ReduceOp::Sum)?,
+        })
+    }
+
+    // <---- Matrices ---->
+    // Tiled product A * B, stored in the given layout
+    #[track_caller]
+    pub fn matmul(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        a: &GpuMatrix,
+        b: &GpuMatrix,
+        layout: Layout,
+    ) -> Result<GpuMatrix, FrameworkError> {
+        if a.columns() != b.rows() {
+            return Err(FrameworkError::DimensionMismatch(format!(
+                "matmul of {}x{} and {}x{} matrices",
+                a.rows(),
+                a.columns(),
+                b.rows(),
+                b.columns()
+            )));
+        }
+        let c: GpuMatrix =
+            GpuMatrix::zeroed(device, a.rows(), b.columns(), layout, Some("matmul_output"))?;
+        let params: Params = Params {
+            rows: c.rows(),
+            columns: c.columns(),
+            inner: a.columns(),
+            ..matrix_strides(a, b, &c)
+        };
+        let workgroups: (u32, u32, u32) = (
+            c.columns().div_ceil(TILE_SIZE),
+            c.rows().div_ceil(TILE_SIZE),
+            1,
+        );
+        self.submit(
+            device,
+            queue,
+            &self.matmul_kernel,
+            &params,
+            &[(0, a.buffer()), (1, b.buffer()), (2, c.buffer())],
+            workgroups,
+        )?;
+        Ok(c)
+    }
+
+    // Transposed copy, in the same layout
+    #[track_caller]
+    pub fn transpose(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        a: &GpuMatrix,
+    ) -> Result<GpuMatrix, FrameworkError> {
+        let c: GpuMatrix = GpuMatrix::zeroed(
+            device,
+            a.columns(),
+            a.rows(),
+            a.layout(),
+            Some("transpose_output"),
+        )?;
+        let params: Params = Params {
+            rows: c.rows(),
+            columns: c.columns(),
+            ..matrix_strides(a, a, &c)
+        };
+        let workgroups: (u32, u32, u32) = (
+            a.columns().div_ceil(TILE_SIZE),
+            a.rows().div_ceil(TILE_SIZE),
+            1,
+        );
+        self.submit(
+            device,
+            queue,
+            &self.transpose_kernel,
+            &params,
+            &[(0, a.buffer()), (2, c.buffer())],
+            workgroups,
+        )?;
+        Ok(c)
+    }
+
+    // A * x
+    #[track_caller]
+    pub fn matvec(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        a: &GpuMatrix,
+        x: &GpuVector,
+    ) -> Result<GpuVector, FrameworkError> {
+        if a.columns() as usize != x.len() {
+            return Err(FrameworkError::DimensionMismatch(format!(
+                "matvec of a {}x{} matrix and a vector of {} elements",
+                a.rows(),
+                a.columns(),
+                x.len()
+            )));
+        }
+        let y: GpuVector = GpuVector::zeroed(device, a.rows() as usize, Some("matvec_output"))?;
+        let (a_row_stride, a_column_stride) = a.strides();
+        let params: Params = Params {
+            rows: a.rows(),
+            inner: a.columns(),
+            a_row_stride,
+            a_column_stride,
+            ..Default::default()
+        };
+        self.submit(
+            device,
+            queue,
+            &self.matvec_kernel,
+            &params,
+            &[(0, a.buffer()), (1, x.buffer()), (2, y.buffer())],
+            self.matvec_kernel.workgroups_for(a.rows()),
+        )?;
+        Ok(y)
+    }
+
+    // <---- Vectors ---->
+    // y = alpha * x + y, x and y must be different vectors
+    #[track_caller]
+    pub fn axpy(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        alpha: f32,
+        x: &GpuVector,
+        y: &GpuVector,
+    ) -> Result<(), FrameworkError> {
+        check_same_len("axpy", x, y)?;
+        // y can't be bound both read-only and writable
+        if x.buffer().global_id() == y.buffer().global_id() {
+            return Err(FrameworkError::AliasedBuffers(
+                "axpy of a vector with itself".to_owned(),
+            ));
+        }
+        let params: Params = Params {
+            rows: x.len() as u32,
+            alpha,
+            ..Default::default()
+        };
+        self.submit(
+            device,
+            queue,
+            &self.axpy_kernel,
+            &params,
+            &[(0, x.buffer()), (2, y.buffer())],
+            self.axpy_kernel.workgroups_for(x.len() as u32),
+        )
+    }
+
+    // Blocks until the result is available
+    #[track_caller]
+    pub fn dot(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        x: &GpuVector,
+        y: &GpuVector,
+    ) -> Result<f32, FrameworkError> {
+        check_same_len("dot", x, y)?;
+        let count: u32 = x.len() as u32;
+        let products: Buffer = create_empty_buffer(
+            device,
+            (x.len().max(1) * size_of::<f32>()) as u64,
+            BufferUsages::STORAGE,
+            Some("dot_products"),
+        )?;
+        let params: Buffer = create_params_buffer(
+            device,
+            &Params {
+                rows: count,
+                ..Default::default()
+            },
+        )?;
+        let bind_group: BindGroup = self.multiply_kernel.create_bind_group(
+            device,
+            &[
+                (0, x.buffer()),
+                (1, y.buffer()),
+                (2, &products),
+                (3, &params),
+            ],
+        )?;
+
+        // Multiply element-wise, then sum
+        let mut encoder: CommandEncoder = create_encoder(device);
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("dot_multiply"),
+                timestamp_writes: None,
+            });
+            self.multiply_kernel.dispatch_with(
+                &mut compute_pass,
+                &bind_group,
+                self.multiply_kernel.workgroups_for(count),
+            );
+        }
+        let sum: Buffer = self.sum.record(device, &mut encoder, &products, count)?;
+        let readback: Readback =
+            Readback::record_buffer_range(device, &mut encoder, &sum, 0, size_of::<f32>() as u64)?;
+        queue.submit(Some(encoder.finish()));
+        Ok(readback.wait::<f32>(device)?[0])
+    }
+
+    // Euclidean norm, blocks until the result is available
+    #[track_caller]
+    pub fn norm(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        x: &GpuVector,
+    ) -> Result<f32, FrameworkError> {
+        Ok(self.dot(device, queue, x, x)?.sqrt())
+    }
+
+    #[track_caller]
+    fn submit(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        kernel: &ComputeKernel,
+        params: &Params,
+        buffers: &[(u32, &Buffer)],
+        workgroups: (u32, u32, u32),
+    ) -> Result<(), FrameworkError> {
+        let params: Buffer = create_params_buffer(device, params)?;
+        let mut bindings: Vec<(u32, &Buffer)> = buffers.to_vec();
+        bindings.push((3, &params));
+        let bind_group: BindGroup = kernel.create_bind_group(device, &bindings)?;
+
+        let mut encoder: CommandEncoder = create_encoder(device);
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: kernel.label(),
+                timestamp_writes: None,
+            });
+            kernel.dispatch_with(&mut compute_pass, &bind_group, workgroups);
+        }
+        queue.submit(Some(encoder.finish()));
+        Ok(())
+    }
+}
+
+// Strides of the A, B and C operands
+fn matrix_strides(a: &GpuMatrix, b: &GpuMatrix, c: &GpuMatrix) -> Params {
+    let (a_row_stride, a_column_stride) = a.strides();
+    let (b_row_stride, b_column_stride) = b.strides();
+    let (c_row_stride, c_column_stride) = c.strides();
+    Params {
+        a_row_stride,
+        a_column_stride,
+        b_row_stride,
+        b_column_stride,
+        c_row_stride,
+        c_column_stride,
+        ..Default::default()
+    }
+}
+
+fn check_same_len(operation: &str, x: &GpuVector, y: &GpuVector) -> Result<(), FrameworkError> {
+    if x.len() != y.len() {
+        return Err(FrameworkError::DimensionMismatch(format!(
+            "{operation} of vectors of {} and {} elements",
+            x.len(),
+            y.len()
+        )));
+    }
+    Ok(())
+}
+
+#[track_caller]
+fn create_params_buffer(device: &Device, params: &Params) -> Result<Buffer, FrameworkError> {
+    create_buffer(
+        device,
+        bytemuck::bytes_of(params),
+        BufferUsages::UNIFORM,
+        Some("linear_algebra_params"),
+    )
+}
+
+fn create_encoder(device: &Device) -> CommandEncoder {
+    device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("linear_algebra_encoder"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::gpu_context::GPUContext,
+        linear_algebra::{
+            gpu_matrix::{GpuMatrix, GpuVector},
+            matrix::{cpu_axpy, cpu_dot, cpu_norm, Layout, Matrix},
+        },
+        verification::{
+            compare::{compare, Tolerance},
+            kernel_check::uniform_f32,
+            verification_context,
+        },
+    };
+
+    use super::LinearAlgebra;
+
+    const LAYOUTS: [Layout; 2] = [Layout::RowMajor, Layout::ColumnMajor];
+
+    fn random_matrix(seed: u64, rows: u32, columns: u32, layout: Layout) -> Matrix {
+        let data: Vec<f32> = uniform_f32(seed, (rows * columns) as usize, -1.0..1.0);
+        Matrix::new(rows, columns, layout, data).expect("Data holds every element")
+    }
+
+    // Sums are accumulated in a different order than the reference
+    fn check(actual: &[f32], expected: &[f32]) -> Result<(), FrameworkError> {
+        let tolerance: Tolerance = Tolerance::new().with_absolute(1e-5).with_relative(1e-5);
+        compare(actual, expected, tolerance).into_result()
+    }
+
+    #[test]
+    fn matmul_matches_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let linear_algebra: LinearAlgebra = LinearAlgebra::new(&context.device)?;
+        // Square, non-square, and sizes that aren't multiples of the 16x16 tile
+        for (rows, inner, columns) in [(16, 16, 16), (1, 1, 1), (5, 37, 3), (40, 17, 33)] {
+            for (a_layout, b_layout, c_layout) in [
+                (Layout::RowMajor, Layout::RowMajor, Layout::RowMajor),
+                (Layout::ColumnMajor, Layout::RowMajor, Layout::ColumnMajor),
+                (Layout::RowMajor, Layout::ColumnMajor, Layout::RowMajor),
+            ] {
+                let a: Matrix = random_matrix(1, rows, inner, a_layout);
+                let b: Matrix = random_matrix(2, inner, columns, b_layout);
+                let gpu_a: GpuMatrix = GpuMatrix::from_matrix(&context.device, &a, Some("a"))?;
+                let gpu_b: GpuMatrix = GpuMatrix::from_matrix(&context.device, &b, Some("b"))?;
+                let c: Matrix = linear_algebra
+                    .matmul(&context.device, &context.queue, &gpu_a, &gpu_b, c_layout)?
+                    .read(&context.device, &context.queue)?;
+                let expected: Matrix = a.matmul(&b, c_layout).expect("Inner dimensions match");
+                assert_eq!((c.rows, c.columns, c.layout), (rows, columns, c_layout));
+                check(&c.data, &expected.data)?;
+            }
+        }
+
+        let a: GpuMatrix = GpuMatrix::from_matrix(
+            &context.device,
+            &random_matrix(3, 4, 5, Layout::RowMajor),
+            None,
+        )?;
+        assert!(matches!(
+            linear_algebra.matmul(&context.device, &context.queue, &a, &a, Layout::RowMajor),
+            Err(FrameworkError::DimensionMismatch(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn transpose_and_matvec_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let linear_algebra: LinearAlgebra = LinearAlgebra::new(&context.device)?;
+        for layout in LAYOUTS {
+            for (rows, columns) in [(1, 1), (16, 16), (3, 50), (35, 18)] {
+                let a: Matrix = random_matrix(4, rows, columns, layout);
+                let gpu_a: GpuMatrix = GpuMatrix::from_matrix(&context.device, &a, Some("a"))?;
+
+                let transposed: Matrix = linear_algebra
+                    .transpose(&context.device, &context.queue, &gpu_a)?
+                    .read(&context.device, &context.queue)?;
+                assert_eq!(transposed, a.transpose(), "{rows}x{columns} {layout:?}");
+
+                let x: Vec<f32> = uniform_f32(5, columns as usize, -1.0..1.0);
+                let gpu_x: GpuVector = GpuVector::from_slice(&context.device, &x, Some("x"))?;
+                let y: Vec<f32> = linear_algebra
+                    .matvec(&context.device, &context.queue, &gpu_a, &gpu_x)?
+                    .read(&context.device, &context.queue)?;
+                check(&y, &a.matvec(&x).expect("Dimensions match"))?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn vector_operations_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let linear_algebra: LinearAlgebra = LinearAlgebra::new(&context.device)?;
+        for len in [1, 63, 64, 1000] {
+            let x: Vec<f32> = uniform_f32(6, len, -1.0..1.0);
+            let mut y: Vec<f32> = uniform_f32(7, len, -1.0..1.0);
+            let gpu_x: GpuVector = GpuVector::from_slice(&context.device, &x, Some("x"))?;
+            let gpu_y: GpuVector = GpuVector::from_slice(&context.device, &y, Some("y"))?;
+
+            let dot: f32 = linear_algebra.dot(&context.device, &context.queue, &gpu_x, &gpu_y)?;
+            check(&[dot], &[cpu_dot(&x, &y)])?;
+            let norm: f32 = linear_algebra.norm(&context.device, &context.queue, &gpu_x)?;
+            check(&[norm], &[cpu_norm(&x)])?;
+
+            linear_algebra.axpy(&context.device, &context.queue, 2.5, &gpu_x, &gpu_y)?;
+            cpu_axpy(2.5, &x, &mut y);
+            check(&gpu_y.read(&context.device, &context.queue)?, &y)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn axpy_rejects_aliased_vectors() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let linear_algebra: LinearAlgebra = LinearAlgebra::new(&context.device)?;
+        let x: GpuVector = GpuVector::from_slice(&context.device, &[1.0, 2.0], Some("x"))?;
+        assert!(matches!(
+            linear_algebra.axpy(&context.device, &context.queue, 1.0, &x, &x),
+            Err(FrameworkError::AliasedBuffers(_))
+        ));
+        let other: GpuVector = GpuVector::from_slice(&context.device, &[1.0], Some("other"))?;
+        assert!(matches!(
+            linear_algebra.axpy(&context.device, &context.queue, 1.0, &x, &other),
+            Err(FrameworkError::DimensionMismatch(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+// Dense f32 linear algebra, matrices are addressed through row and column strides
+// so that every kernel supports both row-major and column-major layouts
+const TILE_SIZE: u32 = 16u;
+const WORKGROUP_SIZE: u32 = 64u;
+
+struct Params {
+    // Output dimensions, or the element count of vector operations
+    rows: u32,
+    columns: u32,
+    // Shared dimension of matrix products
+    inner: u32,
+    a_row_stride: u32,
+    a_column_stride: u32,
+    b_row_stride: u32,
+    b_column_stride: u32,
+    c_row_stride: u32,
+    c_column_stride: u32,
+    alpha: f32,
+}
+
+@group(0) @binding(0)
+var<storage, read> a: array<f32>;
+
+@group(0) @binding(1)
+var<storage, read> b: array<f32>;
+
+@group(0) @binding(2)
+var<storage, read_write> c: array<f32>;
+
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+var<workgroup> tile_a: array<array<f32, TILE_SIZE>, TILE_SIZE>;
+// Padded to avoid bank conflicts when transposing
+var<workgroup> tile_b: array<array<f32, 17>, TILE_SIZE>;
+
+fn a_at(row: u32, column: u32) -> f32 {
+    return a[row * params.a_row_stride + column * params.a_column_stride];
+}
+
+fn b_at(row: u32, column: u32) -> f32 {
+    return b[row * params.b_row_stride + column * params.b_column_stride];
+}
+
+fn c_index(row: u32, column: u32) -> u32 {
+    return row * params.c_row_stride + column * params.c_column_stride;
+}
+
+fn element_index(id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return id.x + (id.y + id.z * num_workgroups.y) * num_workgroups.x * WORKGROUP_SIZE;
+}
+
+// C (rows x columns) = A (rows x inner) * B (inner x columns), one tile of C per workgroup
+@compute @workgroup_size(16, 16)
+fn matmul(
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    let row: u32 = workgroup_id.y * TILE_SIZE + local_id.y;
+    let column: u32 = workgroup_id.x * TILE_SIZE + local_id.x;
+
+    var sum: f32 = 0.0;
+    for (var tile: u32 = 0u; tile < params.inner; tile += TILE_SIZE) {
+        // Load one tile of A and B, zero-padded at the edges
+        let a_column: u32 = tile + local_id.x;
+        let b_row: u32 = tile + local_id.y;
+        var a_value: f32 = 0.0;
+        if row < params.rows && a_column < params.inner {
+            a_value = a_at(row, a_column);
+        }
+        var b_value: f32 = 0.0;
+        if b_row < params.inner && column < params.columns {
+            b_value = b_at(b_row, column);
+        }
+        tile_a[local_id.y][local_id.x] = a_value;
+        tile_b[local_id.y][local_id.x] = b_value;
+        workgroupBarrier();
+
+        for (var i: u32 = 0u; i < TILE_SIZE; i++) {
+            sum += tile_a[local_id.y][i] * tile_b[i][local_id.x];
+        }
+        workgroupBarrier();
+    }
+
+    if row < params.rows && column < params.columns {
+        c[c_index(row, column)] = sum;
+    }
+}
+
+// C (rows x columns) = transpose of A (columns x rows), staged through a tile
+// so that both reads and writes are contiguous
+@compute @workgroup_size(16, 16)
+fn transpose(
+    @builtin(local_invocation_id) local_id: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+) {
+    let a_row: u32 = workgroup_id.y * TILE_SIZE + local_id.y;
+    let a_column: u32 = workgroup_id.x * TILE_SIZE + local_id.x;
+    if a_row < params.columns && a_column < params.rows {
+        tile_b[local_id.y][local_id.x] = a_at(a_row, a_column);
+    }
+    workgroupBarrier();
+
+    let row: u32 = workgroup_id.x * TILE_SIZE + local_id.y;
+    let column: u32 = workgroup_id.y * TILE_SIZE + local_id.x;
+    if row < params.rows && column < params.columns {
+        c[c_index(row, column)] = tile_b[local_id.x][local_id.y];
+    }
+}
+
+// c (rows) = A (rows x inner) * b (inner)
+@compute @workgroup_size(64)
+fn matvec(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let row: u32 = element_index(id, num_workgroups);
+    if row >= params.rows {
+        return;
+    }
+    var sum: f32 = 0.0;
+    for (var i: u32 = 0u; i < params.inner; i++) {
+        sum += a_at(row, i) * b[i];
+    }
+    c[row] = sum;
+}
+
+// c = alpha * a + c
+@compute @workgroup_size(64)
+fn axpy(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index: u32 = element_index(id, num_workgroups);
+    if index >= params.rows {
+        return;
+    }
+    c[index] = params.alpha * a[index] + c[index];
+}
+
+// c = a * b, element-wise
+@compute @workgroup_size(64)
+fn multiply(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index: u32 = element_index(id, num_workgroups);
+    if index >= params.rows {
+        return;
+    }
+    c[index] = a[index] * b[index];
+}
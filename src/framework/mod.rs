@@ -1 +1,10 @@
+pub mod benchmark;
+pub mod compute_app;
+pub mod error;
+pub mod fft;
+pub mod gpu;
+pub mod image_processing;
+pub mod linear_algebra;
+pub mod primitives;
+pub mod verification;
 pub mod windowed_app;
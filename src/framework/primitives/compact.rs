@@ -0,0 +1,231 @@
+use std::{marker::PhantomData, mem::size_of};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    BindGroup, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor, Device, Queue,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        compute_kernel::ComputeKernel, gpu_buffer::GpuBuffer, readback::Readback, scalar::GpuScalar,
+    },
+};
+
+use super::{begin_compute_pass, create_params_buffer, create_scratch_buffer, scan::Scan};
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    _padding: [u32; 3],
+}
+
+// Stream compaction, keeping the elements matching a predicate in their original order
+// -> Elements are flagged, the flags scanned into output positions, then kept elements scattered
+pub struct Compaction<T: GpuScalar> {
+    predicate: String,
+    flag_kernel: ComputeKernel,
+    scatter_kernel: ComputeKernel,
+    scan: Scan<u32>,
+    _element: PhantomData<T>,
+}
+
+impl<T: GpuScalar> Compaction<T> {
+    // The predicate is a WGSL boolean expression of `value`, e.g. "value > 0.5"
+    #[track_caller]
+    pub fn new(device: &Device, predicate: &str) -> Result<Self, FrameworkError> {
+        let source: String = include_str!("shaders/compact.wgsl")
+            .replace("ELEMENT_TYPE", T::WGSL_TYPE)
+            .replace("PREDICATE_EXPRESSION", predicate);
+        Ok(Self {
+            predicate: predicate.to_owned(),
+            flag_kernel: ComputeKernel::builder(&source, "flag")
+                .with_label("compact_flag")
+                .build(device)?,
+            scatter_kernel: ComputeKernel::builder(&source, "scatter")
+                .with_label("compact_scatter")
+                .build(device)?,
+            scan: Scan::new(device)?,
+            _element: PhantomData,
+        })
+    }
+
+    pub fn predicate(&self) -> &str {
+        &self.predicate
+    }
+
+    // Records the compaction of the first `count` elements of `input` into `output`,
+    // which must hold `count` elements, and the number of kept elements into the u32 at
+    // the start of `total`, which must be zeroed
+    #[track_caller]
+    pub fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Buffer,
+        count: u32,
+        output: &Buffer,
+        total: &Buffer,
+    ) -> Result<(), FrameworkError> {
+        let flags: Buffer = create_scratch_buffer::<u32>(device, count, "compact_flags")?;
+        let offsets: Buffer = create_scratch_buffer::<u32>(device, count, "compact_offsets")?;
+        let params: Buffer = create_params_buffer(
+            device,
+            &Params {
+                count,
+                _padding: [0; 3],
+            },
+            "compact_params",
+        )?;
+
+        // Flag kept elements
+        let bind_group: BindGroup = self
+            .flag_kernel
+            .create_bind_group(device, &[(0, input), (1, &flags), (2, &params)])?;
+        {
+            let mut compute_pass = begin_compute_pass(encoder, "compact_flag");
+            self.flag_kernel.dispatch_with(
+                &mut compute_pass,
+                &bind_group,
+                self.flag_kernel.workgroups_for(count),
+            );
+        }
+
+        // Output positions
+        self.scan
+            .record(device, encoder, &flags, &offsets, count, false)?;
+
+        // Scatter
+        let bind_group: BindGroup = self.scatter_kernel.create_bind_group(
+            device,
+            &[
+                (0, input),
+                (2, &params),
+                (3, &offsets),
+                (4, output),
+                (5, total),
+            ],
+        )?;
+        let mut compute_pass = begin_compute_pass(encoder, "compact_scatter");
+        self.scatter_kernel.dispatch_with(
+            &mut compute_pass,
+            &bind_group,
+            self.scatter_kernel.workgroups_for(count),
+        );
+        Ok(())
+    }
+
+    // Blocks until the number of kept elements is known
+    #[track_caller]
+    pub fn compact(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        input: &GpuBuffer<T>,
+    ) -> Result<GpuBuffer<T>, FrameworkError> {
+        let mut output: GpuBuffer<T> = GpuBuffer::zeroed(
+            device,
+            input.len(),
+            BufferUsages::STORAGE,
+            Some("compact_output"),
+        )?;
+        let total: Buffer = create_scratch_buffer::<u32>(device, 1, "compact_total")?;
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("compact_encoder"),
+            });
+        self.record(
+            device,
+            &mut encoder,
+            input.buffer(),
+            input.len() as u32,
+            output.buffer(),
+            &total,
+        )?;
+        let readback: Readback = Readback::record_buffer_range(
+            device,
+            &mut encoder,
+            &total,
+            0,
+            size_of::<u32>() as u64,
+        )?;
+        queue.submit(Some(encoder.finish()));
+
+        let kept: u32 = readback.wait::<u32>(device)?[0];
+        output.resize(device, queue, kept as usize)?;
+        Ok(output)
+    }
+}
+
+// Sequential reference implementation
+pub fn cpu_compact<T: GpuScalar>(values: &[T], predicate: impl Fn(T) -> bool) -> Vec<T> {
+    values
+        .iter()
+        .copied()
+        .filter(|&value| predicate(value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_buffer::GpuBuffer, gpu_context::GPUContext, scalar::GpuScalar},
+        primitives::split_dispatch_context,
+        verification::kernel_check::{uniform_f32, uniform_u32},
+    };
+
+    use super::{cpu_compact, Compaction};
+
+    const COUNTS: [usize; 7] = [0, 1, 200, 256, 700, 1100, 15_000];
+
+    fn gpu_compact<T: GpuScalar>(
+        context: &GPUContext,
+        compaction: &Compaction<T>,
+        values: &[T],
+    ) -> Result<Vec<T>, FrameworkError> {
+        let (device, queue) = (&context.device, &context.queue);
+        let input: GpuBuffer<T> = GpuBuffer::storage(device, values, Some("input"))?;
+        compaction
+            .compact(device, queue, &input)?
+            .read(device, queue)
+    }
+
+    #[test]
+    fn integer_compaction_matches_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        let multiples: Compaction<u32> = Compaction::new(&context.device, "value % 3u == 0u")?;
+        let negatives: Compaction<i32> = Compaction::new(&context.device, "value < 0")?;
+        for count in COUNTS {
+            let values: Vec<u32> = uniform_u32(count as u64, count);
+            assert_eq!(
+                gpu_compact(&context, &multiples, &values)?,
+                cpu_compact(&values, |value| value % 3 == 0),
+                "u32 of {count}"
+            );
+            let signed: Vec<i32> = bytemuck::cast_slice(&values).to_vec();
+            assert_eq!(
+                gpu_compact(&context, &negatives, &signed)?,
+                cpu_compact(&signed, |value| value < 0),
+                "i32 of {count}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn float_compaction_matches_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        let compaction: Compaction<f32> = Compaction::new(&context.device, "value > 0.25")?;
+        for count in COUNTS {
+            let values: Vec<f32> = uniform_f32(count as u64, count, 0.0..1.0);
+            assert_eq!(
+                gpu_compact(&context, &compaction, &values)?,
+                cpu_compact(&values, |value| value > 0.25),
+                "{count}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::mem::size_of;
+
+use bytemuck::Pod;
+use wgpu::{Buffer, BufferUsages, CommandEncoder, ComputePass, ComputePassDescriptor, Device};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::utilities::{create_buffer, create_empty_buffer},
+};
+
+pub mod compact;
+pub mod random;
+pub mod reduce;
+pub mod scan;
+pub mod sort;
+
+// Invocations per workgroup of every primitive kernel, must match the shaders
+const WORKGROUP_SIZE: u32 = 256;
+
+// Intermediate storage buffer of `len` elements (at least one)
+#[track_caller]
+fn create_scratch_buffer<T: Pod>(
+    device: &Device,
+    len: u32,
+    label: &str,
+) -> Result<Buffer, FrameworkError> {
+    create_empty_buffer(
+        device,
+        (len.max(1) as usize * size_of::<T>()) as u64,
+        BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+        Some(label),
+    )
+}
+
+#[track_caller]
+fn create_params_buffer<P: Pod>(
+    device: &Device,
+    params: &P,
+    label: &str,
+) -> Result<Buffer, FrameworkError> {
+    create_buffer(
+        device,
+        bytemuck::bytes_of(params),
+        BufferUsages::UNIFORM,
+        Some(label),
+    )
+}
+
+fn begin_compute_pass<'a>(encoder: &'a mut CommandEncoder, label: &'a str) -> ComputePass<'a> {
+    encoder.begin_compute_pass(&ComputePassDescriptor {
+        label: Some(label),
+        timestamp_writes: None,
+    })
+}
+
+// Software context whose workgroup limit splits dispatches of more than 4 workgroups over
+// several dimensions, so that surplus workgroups are exercised by small inputs
+#[cfg(test)]
+fn split_dispatch_context() -> crate::framework::gpu::gpu_context::GPUContext {
+    use crate::framework::{
+        gpu::device_requirements::DeviceRequirements,
+        verification::verification_context_with_requirements,
+    };
+
+    verification_context_with_requirements(&DeviceRequirements::new().with_required_limits(
+        wgpu::Limits {
+            max_compute_workgroups_per_dimension: 4,
+            ..Default::default()
+        },
+    ))
+    .expect("Software adapter is available")
+}
@@ -0,0 +1,426 @@
+use std::f32::consts::TAU;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, Buffer, CommandEncoder, CommandEncoderDescriptor, Device, Queue};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{compute_kernel::ComputeKernel, gpu_buffer::GpuBuffer},
+};
+
+use super::{begin_compute_pass, create_params_buffer};
+
+// WGSL functions of the generator, prepend to a kernel's source to generate random
+// numbers inside it: philox4x32(counter, key), random_uniform_f32(bits) and
+// random_normal_f32x2(bits)
+pub const PHILOX_WGSL: &str = include_str!("shaders/philox.wgsl");
+
+// Must match philox.wgsl
+const PHILOX_M0: u32 = 0xD2511F53;
+const PHILOX_M1: u32 = 0xCD9E8D57;
+const PHILOX_W0: u32 = 0x9E3779B9;
+const PHILOX_W1: u32 = 0xBB67AE85;
+// Random values generated per invocation
+const BLOCK_SIZE: u32 = 4;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    seed_low: u32,
+    seed_high: u32,
+    stream_low: u32,
+    stream_high: u32,
+    mean: f32,
+    std_dev: f32,
+    _padding: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distribution {
+    // Any u32
+    UniformU32,
+    // In [0, 1)
+    UniformF32,
+    Normal { mean: f32, std_dev: f32 },
+}
+
+// Fills storage buffers with random values from the Philox4x32-10 generator
+// -> Values only depend on the seed, the stream and their index, so fills are reproducible
+//    across runs and adapters, and any element can be recomputed on the CPU
+// -> Filling again with the same seed and stream gives the same values, use a new stream
+//    (e.g. the iteration number) for fresh ones
+// -> Integer and uniform float values are bit-exact, normal values may differ by a few ULPs
+//    between adapters
+pub struct RandomGenerator {
+    seed: u64,
+    stream: u64,
+    fill_u32_kernel: ComputeKernel,
+    fill_uniform_f32_kernel: ComputeKernel,
+    fill_normal_f32_kernel: ComputeKernel,
+}
+
+impl RandomGenerator {
+    #[track_caller]
+    pub fn new(device: &Device, seed: u64) -> Result<Self, FrameworkError> {
+        let source: String = format!("{PHILOX_WGSL}\n{}", include_str!("shaders/random.wgsl"));
+        let kernel = |entry_point: &str| {
+            ComputeKernel::builder(&source, entry_point)
+                .with_label(entry_point)
+                .build(device)
+        };
+        Ok(Self {
+            seed,
+            stream: 0,
+            fill_u32_kernel: kernel("fill_u32")?,
+            fill_uniform_f32_kernel: kernel("fill_uniform_f32")?,
+            fill_normal_f32_kernel: kernel("fill_normal_f32")?,
+        })
+    }
+
+    pub fn with_stream(mut self, stream: u64) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&self) -> u64 {
+        self.stream
+    }
+
+    pub fn set_stream(&mut self, stream: u64) {
+        self.stream = stream;
+    }
+
+    // <---- Filling ---->
+    #[track_caller]
+    pub fn fill_u32(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        buffer: &GpuBuffer<u32>,
+    ) -> Result<(), FrameworkError> {
+        self.submit(
+            device,
+            queue,
+            buffer.buffer(),
+            buffer.len(),
+            Distribution::UniformU32,
+        )
+    }
+
+    #[track_caller]
+    pub fn fill_uniform_f32(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        buffer: &GpuBuffer<f32>,
+    ) -> Result<(), FrameworkError> {
+        self.submit(
+            device,
+            queue,
+            buffer.buffer(),
+            buffer.len(),
+            Distribution::UniformF32,
+        )
+    }
+
+    #[track_caller]
+    pub fn fill_normal_f32(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        buffer: &GpuBuffer<f32>,
+        mean: f32,
+        std_dev: f32,
+    ) -> Result<(), FrameworkError> {
+        let distribution: Distribution = Distribution::Normal { mean, std_dev };
+        self.submit(device, queue, buffer.buffer(), buffer.len(), distribution)
+    }
+
+    #[track_caller]
+    fn submit(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        buffer: &Buffer,
+        count: usize,
+        distribution: Distribution,
+    ) -> Result<(), FrameworkError> {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("random_encoder"),
+            });
+        self.record(device, &mut encoder, buffer, count as u32, distribution)?;
+        queue.submit(Some(encoder.finish()));
+        Ok(())
+    }
+
+    // <---- Recording ---->
+    // Fills the first `count` elements of a storage buffer of u32 or f32
+    #[track_caller]
+    pub fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        buffer: &Buffer,
+        count: u32,
+        distribution: Distribution,
+    ) -> Result<(), FrameworkError> {
+        if count == 0 {
+            return Ok(());
+        }
+        let (kernel, mean, std_dev): (&ComputeKernel, f32, f32) = match distribution {
+            Distribution::UniformU32 => (&self.fill_u32_kernel, 0.0, 0.0),
+            Distribution::UniformF32 => (&self.fill_uniform_f32_kernel, 0.0, 0.0),
+            Distribution::Normal { mean, std_dev } => (&self.fill_normal_f32_kernel, mean, std_dev),
+        };
+        let params: Buffer = create_params_buffer(
+            device,
+            &Params {
+                count,
+                seed_low: self.seed as u32,
+                seed_high: (self.seed >> 32) as u32,
+                stream_low: self.stream as u32,
+                stream_high: (self.stream >> 32) as u32,
+                mean,
+                std_dev,
+                _padding: 0,
+            },
+            "random_params",
+        )?;
+        let bind_group: BindGroup =
+            kernel.create_bind_group(device, &[(0, buffer), (1, &params)])?;
+        let mut compute_pass = begin_compute_pass(encoder, "random_fill");
+        kernel.dispatch_with(
+            &mut compute_pass,
+            &bind_group,
+            kernel.workgroups_for(count.div_ceil(BLOCK_SIZE)),
+        );
+        Ok(())
+    }
+}
+
+// Reference implementations, generating the same values as a RandomGenerator with this
+// seed and stream
+pub fn cpu_philox4x32(counter: [u32; 4], key: [u32; 2]) -> [u32; 4] {
+    let mut state: [u32; 4] = counter;
+    let mut key: [u32; 2] = key;
+    for _round in 0..10 {
+        let product_0: u64 = PHILOX_M0 as u64 * state[0] as u64;
+        let product_1: u64 = PHILOX_M1 as u64 * state[2] as u64;
+        state = [
+            (product_1 >> 32) as u32 ^ state[1] ^ key[0],
+            product_1 as u32,
+            (product_0 >> 32) as u32 ^ state[3] ^ key[1],
+            product_0 as u32,
+        ];
+        key = [
+            key[0].wrapping_add(PHILOX_W0),
+            key[1].wrapping_add(PHILOX_W1),
+        ];
+    }
+    state
+}
+
+fn cpu_random_bits(seed: u64, stream: u64, count: usize, distribution: Distribution) -> Vec<u32> {
+    let key: [u32; 2] = [seed as u32, (seed >> 32) as u32];
+    (0..count.div_ceil(BLOCK_SIZE as usize))
+        .flat_map(|block| {
+            let counter: [u32; 4] = [block as u32, stream as u32, (stream >> 32) as u32, 0];
+            let bits: [u32; 4] = cpu_philox4x32(counter, key);
+            match distribution {
+                Distribution::UniformU32 => bits,
+                Distribution::UniformF32 => bits.map(|bits| uniform_f32(bits).to_bits()),
+                Distribution::Normal { mean, std_dev } => {
+                    let [n0, n1] = normal_f32x2(bits[0], bits[1]);
+                    let [n2, n3] = normal_f32x2(bits[2], bits[3]);
+                    [n0, n1, n2, n3].map(|normal| (mean + std_dev * normal).to_bits())
+                }
+            }
+        })
+        .take(count)
+        .collect()
+}
+
+pub fn cpu_random_u32(seed: u64, stream: u64, count: usize) -> Vec<u32> {
+    cpu_random_bits(seed, stream, count, Distribution::UniformU32)
+}
+
+// Normal values may differ from the GPU's by a few ULPs, as ln, sin and cos aren't correctly
+// rounded on every adapter
+pub fn cpu_random_f32(
+    seed: u64,
+    stream: u64,
+    count: usize,
+    distribution: Distribution,
+) -> Vec<f32> {
+    bytemuck::cast_vec(cpu_random_bits(seed, stream, count, distribution))
+}
+
+fn uniform_f32(bits: u32) -> f32 {
+    (bits >> 8) as f32 / (1 << 24) as f32
+}
+
+fn normal_f32x2(bits_0: u32, bits_1: u32) -> [f32; 2] {
+    let u1: f32 = ((bits_0 >> 8) + 1) as f32 / (1 << 24) as f32;
+    let radius: f32 = (-2.0 * u1.ln()).sqrt();
+    let (sin, cos): (f32, f32) = (TAU * uniform_f32(bits_1)).sin_cos();
+    [radius * cos, radius * sin]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_buffer::GpuBuffer, gpu_context::GPUContext},
+        primitives::split_dispatch_context,
+        verification::{
+            compare::{compare, Comparison, Tolerance},
+            kernel_check::{uniform_u32, KernelCheck, KernelInputs},
+            verification_context,
+        },
+    };
+
+    use super::{
+        cpu_philox4x32, cpu_random_f32, cpu_random_u32, Distribution, RandomGenerator, PHILOX_WGSL,
+    };
+
+    // A partial block, several workgroups, and 9 workgroups dispatched as 16 when split
+    const COUNTS: [usize; 5] = [1, 6, 1000, 5001, 9000];
+    const SEED: u64 = 0x0123_4567_89AB_CDEF;
+    const STREAM: u64 = 0xFEDC_BA98_7654_3210;
+
+    // Bits of the generated values
+    fn gpu_random(
+        context: &GPUContext,
+        generator: &RandomGenerator,
+        count: usize,
+        distribution: Distribution,
+    ) -> Result<Vec<u32>, FrameworkError> {
+        let (device, queue) = (&context.device, &context.queue);
+        if distribution == Distribution::UniformU32 {
+            let buffer: GpuBuffer<u32> = GpuBuffer::storage(device, &vec![0; count], None)?;
+            generator.fill_u32(device, queue, &buffer)?;
+            return buffer.read(device, queue);
+        }
+        let buffer: GpuBuffer<f32> = GpuBuffer::storage(device, &vec![0.0; count], None)?;
+        match distribution {
+            Distribution::Normal { mean, std_dev } => {
+                generator.fill_normal_f32(device, queue, &buffer, mean, std_dev)?
+            }
+            _ => generator.fill_uniform_f32(device, queue, &buffer)?,
+        }
+        Ok(bytemuck::cast_vec(buffer.read(device, queue)?))
+    }
+
+    #[test]
+    fn uniform_values_match_cpu_exactly() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        let generator: RandomGenerator =
+            RandomGenerator::new(&context.device, SEED)?.with_stream(STREAM);
+        for count in COUNTS {
+            assert_eq!(
+                gpu_random(&context, &generator, count, Distribution::UniformU32)?,
+                cpu_random_u32(SEED, STREAM, count),
+                "u32 of {count}"
+            );
+            let expected: Vec<f32> = cpu_random_f32(SEED, STREAM, count, Distribution::UniformF32);
+            assert_eq!(
+                gpu_random(&context, &generator, count, Distribution::UniformF32)?,
+                bytemuck::cast_vec::<f32, u32>(expected),
+                "uniform f32 of {count}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn normal_values_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        let generator: RandomGenerator =
+            RandomGenerator::new(&context.device, SEED)?.with_stream(STREAM);
+        let distribution: Distribution = Distribution::Normal {
+            mean: 2.0,
+            std_dev: 0.5,
+        };
+        for count in COUNTS {
+            let actual: Vec<f32> =
+                bytemuck::cast_vec(gpu_random(&context, &generator, count, distribution)?);
+            // A few ULPs, or an absolute error for values close to zero
+            compare(
+                &actual,
+                &cpu_random_f32(SEED, STREAM, count, distribution),
+                Tolerance::new().with_ulps(8).with_absolute(1e-6),
+            )
+            .into_result()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn streams_and_dispatches() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let split: GPUContext = split_dispatch_context();
+        let count: usize = 9000;
+        let fill = |context: &GPUContext, stream: u64| -> Result<Vec<u32>, FrameworkError> {
+            let generator: RandomGenerator =
+                RandomGenerator::new(&context.device, SEED)?.with_stream(stream);
+            gpu_random(context, &generator, count, Distribution::UniformU32)
+        };
+
+        let values: Vec<u32> = fill(&context, STREAM)?;
+        assert_eq!(fill(&context, STREAM)?, values);
+        assert_eq!(fill(&split, STREAM)?, values);
+        let other_stream: Vec<u32> = fill(&context, STREAM + 1)?;
+        let same: usize = (values.iter())
+            .zip(&other_stream)
+            .filter(|(a, b)| a == b)
+            .count();
+        assert!(same < 4, "{same} equal values in different streams");
+        Ok(())
+    }
+
+    #[test]
+    fn philox_wgsl_matches_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let source: String = format!(
+            "{PHILOX_WGSL}
+            @group(0) @binding(0)
+            var<storage, read> counters: array<vec4<u32>>;
+
+            @group(0) @binding(1)
+            var<uniform> key: vec2<u32>;
+
+            @group(0) @binding(2)
+            var<storage, read_write> output: array<vec4<u32>>;
+
+            @compute @workgroup_size(64)
+            fn main(@builtin(global_invocation_id) id: vec3<u32>) {{
+                if id.x < arrayLength(&output) {{
+                    output[id.x] = philox4x32(counters[id.x], key);
+                }}
+            }}
+            "
+        );
+        let count: usize = 300;
+        let counters: Vec<u32> = uniform_u32(7, count * 4);
+        let comparison: Comparison<[u32; 4]> = KernelCheck::new(&source, "main")
+            .with_label("philox")
+            .with_input(0, &counters)
+            .with_uniform(1, &[0xDEAD_BEEFu32, 0x0BAD_F00D])
+            .with_output(2, count)
+            .run(&context, |inputs: &KernelInputs| {
+                let key: [u32; 2] = inputs.uniform(1);
+                inputs
+                    .get::<[u32; 4]>(0)
+                    .iter()
+                    .map(|&counter| cpu_philox4x32(counter, key))
+                    .collect()
+            })?;
+        comparison.into_result()
+    }
+}
@@ -0,0 +1,266 @@
+use std::{marker::PhantomData, mem::size_of};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, Buffer, CommandEncoder, CommandEncoderDescriptor, Device, Queue};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        compute_kernel::ComputeKernel, gpu_buffer::GpuBuffer, readback::Readback, scalar::GpuScalar,
+    },
+};
+
+use super::{begin_compute_pass, create_params_buffer, create_scratch_buffer, WORKGROUP_SIZE};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReduceOp {
+    Sum,
+    Min,
+    Max,
+}
+
+impl ReduceOp {
+    // Result of reducing no elements
+    pub fn identity<T: GpuScalar>(self) -> T {
+        match self {
+            Self::Sum => T::ZERO,
+            Self::Min => T::HIGHEST,
+            Self::Max => T::LOWEST,
+        }
+    }
+
+    pub fn combine<T: GpuScalar>(self, a: T, b: T) -> T {
+        match self {
+            Self::Sum => a.add(b),
+            Self::Min => match b < a {
+                true => b,
+                false => a,
+            },
+            Self::Max => match b > a {
+                true => b,
+                false => a,
+            },
+        }
+    }
+
+    fn wgsl_expression(self) -> &'static str {
+        match self {
+            Self::Sum => "a + b",
+            Self::Min => "min(a, b)",
+            Self::Max => "max(a, b)",
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    group_count: u32,
+    _padding: [u32; 2],
+}
+
+// Sum, minimum or maximum of a storage buffer of any length
+// -> Each level reduces 512 elements per workgroup, until a single element remains
+// -> Floating point sums are computed in a different order than cpu_reduce,
+//    so they may differ in the last bits
+pub struct Reduction<T: GpuScalar> {
+    op: ReduceOp,
+    kernel: ComputeKernel,
+    _element: PhantomData<T>,
+}
+
+impl<T: GpuScalar> Reduction<T> {
+    #[track_caller]
+    pub fn new(device: &Device, op: ReduceOp) -> Result<Self, FrameworkError> {
+        let source: String = include_str!("shaders/reduce.wgsl")
+            .replace("ELEMENT_TYPE", T::WGSL_TYPE)
+            .replace("IDENTITY_VALUE", &op.identity::<T>().to_wgsl())
+            .replace("COMBINE_EXPRESSION", op.wgsl_expression());
+        let kernel: ComputeKernel = ComputeKernel::builder(&source, "reduce")
+            .with_label("reduce")
+            .build(device)?;
+        Ok(Self {
+            op,
+            kernel,
+            _element: PhantomData,
+        })
+    }
+
+    pub fn op(&self) -> ReduceOp {
+        self.op
+    }
+
+    // Records the reduction of the first `count` elements of `input`,
+    // the result is element 0 of the returned buffer once submitted
+    #[track_caller]
+    pub fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Buffer,
+        count: u32,
+    ) -> Result<Buffer, FrameworkError> {
+        let mut partials: Option<Buffer> = None;
+        let mut count: u32 = count;
+        loop {
+            let workgroups: u32 = count.div_ceil(2 * WORKGROUP_SIZE).max(1);
+            let output: Buffer = create_scratch_buffer::<T>(device, workgroups, "reduce_partials")?;
+            let params: Buffer = create_params_buffer(
+                device,
+                &Params {
+                    count,
+                    group_count: workgroups,
+                    _padding: [0; 2],
+                },
+                "reduce_params",
+            )?;
+            let bind_group: BindGroup = self.kernel.create_bind_group(
+                device,
+                &[
+                    (0, partials.as_ref().unwrap_or(input)),
+                    (1, &output),
+                    (2, &params),
+                ],
+            )?;
+            {
+                let mut compute_pass = begin_compute_pass(encoder, "reduce");
+                self.kernel.dispatch_with(
+                    &mut compute_pass,
+                    &bind_group,
+                    self.kernel.workgroups_for(workgroups * WORKGROUP_SIZE),
+                );
+            }
+
+            if workgroups == 1 {
+                return Ok(output);
+            }
+            partials = Some(output);
+            count = workgroups;
+        }
+    }
+
+    // Blocks until the result is available
+    #[track_caller]
+    pub fn reduce(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        input: &GpuBuffer<T>,
+    ) -> Result<T, FrameworkError> {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("reduce_encoder"),
+            });
+        let result: Buffer =
+            self.record(device, &mut encoder, input.buffer(), input.len() as u32)?;
+        let readback: Readback =
+            Readback::record_buffer_range(device, &mut encoder, &result, 0, size_of::<T>() as u64)?;
+        queue.submit(Some(encoder.finish()));
+        Ok(readback.wait::<T>(device)?[0])
+    }
+}
+
+// Reference implementation, combining pairwise so that floating point sums keep
+// the same accuracy as the GPU's tree reduction
+pub fn cpu_reduce<T: GpuScalar>(values: &[T], op: ReduceOp) -> T {
+    match values {
+        [] => op.identity(),
+        [value] => *value,
+        _ => {
+            let (left, right) = values.split_at(values.len() / 2);
+            op.combine(cpu_reduce(left, op), cpu_reduce(right, op))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_buffer::GpuBuffer, gpu_context::GPUContext, scalar::GpuScalar},
+        primitives::split_dispatch_context,
+        verification::kernel_check::{uniform_f32, uniform_u32},
+    };
+
+    use super::{cpu_reduce, ReduceOp, Reduction};
+
+    const OPS: [ReduceOp; 3] = [ReduceOp::Sum, ReduceOp::Min, ReduceOp::Max];
+
+    // One workgroup, several, a non-multiple of 512, and 5 workgroups dispatched as 6
+    const COUNTS: [usize; 7] = [0, 1, 300, 512, 1000, 2100, 20_000];
+
+    fn gpu_reduce<T: GpuScalar>(
+        context: &GPUContext,
+        values: &[T],
+        op: ReduceOp,
+    ) -> Result<T, FrameworkError> {
+        let reduction: Reduction<T> = Reduction::new(&context.device, op)?;
+        let input: GpuBuffer<T> = GpuBuffer::storage(&context.device, values, Some("input"))?;
+        reduction.reduce(&context.device, &context.queue, &input)
+    }
+
+    #[test]
+    fn integer_reductions_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        for count in COUNTS {
+            let values: Vec<u32> = uniform_u32(count as u64, count);
+            let signed: Vec<i32> = bytemuck::cast_slice(&values).to_vec();
+            for op in OPS {
+                assert_eq!(
+                    gpu_reduce(&context, &values, op)?,
+                    cpu_reduce(&values, op),
+                    "u32 {op:?} of {count}"
+                );
+                assert_eq!(
+                    gpu_reduce(&context, &signed, op)?,
+                    cpu_reduce(&signed, op),
+                    "i32 {op:?} of {count}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn float_reductions_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        for count in COUNTS {
+            // Integers, so that sums are exact in any order
+            let values: Vec<f32> = uniform_f32(count as u64, count, -100.0..100.0)
+                .into_iter()
+                .map(f32::round)
+                .collect();
+            for op in OPS {
+                assert_eq!(
+                    gpu_reduce(&context, &values, op)?,
+                    cpu_reduce(&values, op),
+                    "{op:?} of {count}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn float_identities_are_infinite() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        assert_eq!(ReduceOp::Min.identity::<f32>(), f32::INFINITY);
+        assert_eq!(ReduceOp::Max.identity::<f32>(), f32::NEG_INFINITY);
+        for (values, op, expected) in [
+            (vec![f32::INFINITY], ReduceOp::Min, f32::INFINITY),
+            (vec![f32::NEG_INFINITY], ReduceOp::Max, f32::NEG_INFINITY),
+            (vec![f32::INFINITY; 700], ReduceOp::Min, f32::INFINITY),
+            (vec![], ReduceOp::Min, f32::INFINITY),
+            (vec![], ReduceOp::Max, f32::NEG_INFINITY),
+        ] {
+            assert_eq!(cpu_reduce(&values, op), expected);
+            assert_eq!(
+                gpu_reduce(&context, &values, op)?,
+                expected,
+                "{op:?} of {values:?}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+use std::marker::PhantomData;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    BindGroup, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor, Device, Queue,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{compute_kernel::ComputeKernel, gpu_buffer::GpuBuffer, scalar::GpuScalar},
+};
+
+use super::{begin_compute_pass, create_params_buffer, create_scratch_buffer, WORKGROUP_SIZE};
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    inclusive: u32,
+    block_count: u32,
+    _padding: u32,
+}
+
+// Inclusive or exclusive prefix sums of a storage buffer of any length
+// -> Blocks of 256 elements are scanned independently, then offset by the
+//    (recursively scanned) sums of the blocks before them
+pub struct Scan<T: GpuScalar> {
+    scan_kernel: ComputeKernel,
+    offsets_kernel: ComputeKernel,
+    _element: PhantomData<T>,
+}
+
+impl<T: GpuScalar> Scan<T> {
+    #[track_caller]
+    pub fn new(device: &Device) -> Result<Self, FrameworkError> {
+        let source: String = include_str!("shaders/scan.wgsl")
+            .replace("ELEMENT_TYPE", T::WGSL_TYPE)
+            .replace("ZERO_VALUE", &T::ZERO.to_wgsl());
+        Ok(Self {
+            scan_kernel: ComputeKernel::builder(&source, "scan_blocks")
+                .with_label("scan_blocks")
+                .build(device)?,
+            offsets_kernel: ComputeKernel::builder(&source, "add_block_offsets")
+                .with_label("scan_add_block_offsets")
+                .build(device)?,
+            _element: PhantomData,
+        })
+    }
+
+    // Records the scan of the first `count` elements of `input` into `output`,
+    // which must be a different buffer
+    #[track_caller]
+    pub fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        input: &Buffer,
+        output: &Buffer,
+        count: u32,
+        inclusive: bool,
+    ) -> Result<(), FrameworkError> {
+        let blocks: u32 = count.div_ceil(WORKGROUP_SIZE);
+        let block_sums: Buffer = create_scratch_buffer::<T>(device, blocks, "scan_block_sums")?;
+        let params: Buffer = create_params_buffer(
+            device,
+            &Params {
+                count,
+                inclusive: inclusive as u32,
+                block_count: blocks,
+                _padding: 0,
+            },
+            "scan_params",
+        )?;
+
+        // Scan blocks
+        let bind_group: BindGroup = self.scan_kernel.create_bind_group(
+            device,
+            &[(0, input), (1, output), (2, &block_sums), (3, &params)],
+        )?;
+        {
+            let mut compute_pass = begin_compute_pass(encoder, "scan_blocks");
+            self.scan_kernel.dispatch_with(
+                &mut compute_pass,
+                &bind_group,
+                self.scan_kernel.workgroups_for(count),
+            );
+        }
+        if blocks <= 1 {
+            return Ok(());
+        }
+
+        // Offset blocks by the sum of every block before them
+        let block_offsets: Buffer =
+            create_scratch_buffer::<T>(device, blocks, "scan_block_offsets")?;
+        self.record(device, encoder, &block_sums, &block_offsets, blocks, false)?;
+        let bind_group: BindGroup = self
+            .offsets_kernel
+            .create_bind_group(device, &[(1, output), (3, &params), (4, &block_offsets)])?;
+        let mut compute_pass = begin_compute_pass(encoder, "scan_add_block_offsets");
+        self.offsets_kernel.dispatch_with(
+            &mut compute_pass,
+            &bind_group,
+            self.offsets_kernel.workgroups_for(count),
+        );
+        Ok(())
+    }
+
+    #[track_caller]
+    pub fn inclusive(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        input: &GpuBuffer<T>,
+    ) -> Result<GpuBuffer<T>, FrameworkError> {
+        self.scan(device, queue, input, true)
+    }
+
+    #[track_caller]
+    pub fn exclusive(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        input: &GpuBuffer<T>,
+    ) -> Result<GpuBuffer<T>, FrameworkError> {
+        self.scan(device, queue, input, false)
+    }
+
+    #[track_caller]
+    fn scan(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        input: &GpuBuffer<T>,
+        inclusive: bool,
+    ) -> Result<GpuBuffer<T>, FrameworkError> {
+        let output: GpuBuffer<T> = GpuBuffer::zeroed(
+            device,
+            input.len(),
+            BufferUsages::STORAGE,
+            Some("scan_output"),
+        )?;
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("scan_encoder"),
+            });
+        self.record(
+            device,
+            &mut encoder,
+            input.buffer(),
+            output.buffer(),
+            input.len() as u32,
+            inclusive,
+        )?;
+        queue.submit(Some(encoder.finish()));
+        Ok(output)
+    }
+}
+
+// Sequential reference implementations
+pub fn cpu_inclusive_scan<T: GpuScalar>(values: &[T]) -> Vec<T> {
+    values
+        .iter()
+        .scan(T::ZERO, |sum, &value| {
+            *sum = sum.add(value);
+            Some(*sum)
+        })
+        .collect()
+}
+
+pub fn cpu_exclusive_scan<T: GpuScalar>(values: &[T]) -> Vec<T> {
+    values
+        .iter()
+        .scan(T::ZERO, |sum, &value| {
+            let previous: T = *sum;
+            *sum = sum.add(value);
+            Some(previous)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_buffer::GpuBuffer, gpu_context::GPUContext, scalar::GpuScalar},
+        primitives::split_dispatch_context,
+        verification::{
+            compare::{compare, Tolerance},
+            kernel_check::{uniform_f32, uniform_u32},
+        },
+    };
+
+    use super::{cpu_exclusive_scan, cpu_inclusive_scan, Scan};
+
+    // One block, several, a non-multiple of 256, 5 blocks dispatched as 6, and block sums
+    // scanned over several levels
+    const COUNTS: [usize; 7] = [0, 1, 200, 256, 700, 1100, 15_000];
+
+    // Inclusive and exclusive scans
+    fn gpu_scans<T: GpuScalar>(
+        context: &GPUContext,
+        values: &[T],
+    ) -> Result<(Vec<T>, Vec<T>), FrameworkError> {
+        let (device, queue) = (&context.device, &context.queue);
+        let scan: Scan<T> = Scan::new(device)?;
+        let input: GpuBuffer<T> = GpuBuffer::storage(device, values, Some("input"))?;
+        Ok((
+            scan.inclusive(device, queue, &input)?.read(device, queue)?,
+            scan.exclusive(device, queue, &input)?.read(device, queue)?,
+        ))
+    }
+
+    #[test]
+    fn integer_scans_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        for count in COUNTS {
+            let values: Vec<u32> = uniform_u32(count as u64, count)
+                .into_iter()
+                .map(|value| value % 1000)
+                .collect();
+            let (inclusive, exclusive) = gpu_scans(&context, &values)?;
+            assert_eq!(inclusive, cpu_inclusive_scan(&values), "u32 of {count}");
+            assert_eq!(exclusive, cpu_exclusive_scan(&values), "u32 of {count}");
+
+            // Sums going up and down through zero
+            let signed: Vec<i32> = values.iter().map(|&value| value as i32 - 520).collect();
+            let (inclusive, exclusive) = gpu_scans(&context, &signed)?;
+            assert_eq!(inclusive, cpu_inclusive_scan(&signed), "i32 of {count}");
+            assert_eq!(exclusive, cpu_exclusive_scan(&signed), "i32 of {count}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn float_scans_match_cpu() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        for count in COUNTS {
+            let values: Vec<f32> = uniform_f32(count as u64, count, -1.0..1.0);
+            // Summation order differs, each partial sum accumulates rounding errors
+            let tolerance: Tolerance = Tolerance::new().with_absolute(1e-6 * count as f64);
+            let (inclusive, exclusive) = gpu_scans(&context, &values)?;
+            compare(&inclusive, &cpu_inclusive_scan(&values), tolerance).into_result()?;
+            compare(&exclusive, &cpu_exclusive_scan(&values), tolerance).into_result()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+// Specialised before compilation: ELEMENT_TYPE and PREDICATE_EXPRESSION (of value)
+alias Element = ELEMENT_TYPE;
+
+struct Params {
+    count: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read> input: array<Element>;
+
+@group(0) @binding(1)
+var<storage, read_write> flags: array<u32>;
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+// Exclusive scan of flags
+@group(0) @binding(3)
+var<storage, read> offsets: array<u32>;
+
+@group(0) @binding(4)
+var<storage, read_write> output: array<Element>;
+
+// Number of elements kept
+@group(0) @binding(5)
+var<storage, read_write> total: array<u32>;
+
+fn keep(value: Element) -> bool {
+    return PREDICATE_EXPRESSION;
+}
+
+fn element_index(id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return id.x + (id.y + id.z * num_workgroups.y) * num_workgroups.x * 256u;
+}
+
+@compute @workgroup_size(256)
+fn flag(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index: u32 = element_index(id, num_workgroups);
+    if index >= params.count {
+        return;
+    }
+    flags[index] = select(0u, 1u, keep(input[index]));
+}
+
+@compute @workgroup_size(256)
+fn scatter(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let index: u32 = element_index(id, num_workgroups);
+    if index >= params.count {
+        return;
+    }
+    let value: Element = input[index];
+    let kept: bool = keep(value);
+    if kept {
+        output[offsets[index]] = value;
+    }
+    if index == params.count - 1u {
+        total[0] = offsets[index] + select(0u, 1u, kept);
+    }
+}
@@ -0,0 +1,54 @@
+// Philox4x32-10 counter-based random number generator (Salmon et al., "Parallel random
+// numbers: as easy as 1, 2, 3"), prepend to a kernel's source through PHILOX_WGSL
+// -> Every (counter, key) pair maps to 4 independent random u32, so invocations need no
+//    state: use e.g. the element index as the counter and the seed as the key
+// -> Integer outputs are bit-exact on every adapter, floating point conversions are not
+//    guaranteed to be for the normal distribution (log, sqrt, cos and sin)
+const PHILOX_M0: u32 = 0xD2511F53u;
+const PHILOX_M1: u32 = 0xCD9E8D57u;
+const PHILOX_W0: u32 = 0x9E3779B9u;
+const PHILOX_W1: u32 = 0xBB67AE85u;
+
+// High 32 bits of the 64-bit product, from 16-bit halves as WGSL has no wide multiply
+fn philox_mul_hi(a: u32, b: u32) -> u32 {
+    let a_low: u32 = a & 0xFFFFu;
+    let a_high: u32 = a >> 16u;
+    let b_low: u32 = b & 0xFFFFu;
+    let b_high: u32 = b >> 16u;
+    let high_low: u32 = a_high * b_low;
+    let cross: u32 = ((a_low * b_low) >> 16u) + (high_low & 0xFFFFu) + a_low * b_high;
+    return a_high * b_high + (high_low >> 16u) + (cross >> 16u);
+}
+
+fn philox_round(counter: vec4<u32>, key: vec2<u32>) -> vec4<u32> {
+    return vec4<u32>(
+        philox_mul_hi(PHILOX_M1, counter.z) ^ counter.y ^ key.x,
+        PHILOX_M1 * counter.z,
+        philox_mul_hi(PHILOX_M0, counter.x) ^ counter.w ^ key.y,
+        PHILOX_M0 * counter.x,
+    );
+}
+
+fn philox4x32(counter: vec4<u32>, key: vec2<u32>) -> vec4<u32> {
+    var state: vec4<u32> = counter;
+    var round_key: vec2<u32> = key;
+    for (var round: u32 = 0u; round < 10u; round++) {
+        state = philox_round(state, round_key);
+        round_key += vec2<u32>(PHILOX_W0, PHILOX_W1);
+    }
+    return state;
+}
+
+// Uniform in [0, 1), exactly representable multiples of 2^-24
+fn random_uniform_f32(bits: u32) -> f32 {
+    return f32(bits >> 8u) * (1.0 / 16777216.0);
+}
+
+// Two independent standard normal values (Box-Muller)
+fn random_normal_f32x2(bits: vec2<u32>) -> vec2<f32> {
+    // In (0, 1] so that the logarithm is finite
+    let u1: f32 = f32((bits.x >> 8u) + 1u) * (1.0 / 16777216.0);
+    let angle: f32 = 6.283185307179586 * random_uniform_f32(bits.y);
+    let radius: f32 = sqrt(-2.0 * log(u1));
+    return radius * vec2<f32>(cos(angle), sin(angle));
+}
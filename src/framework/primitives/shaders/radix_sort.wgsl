@@ -0,0 +1,166 @@
+// Least significant digit radix sort pass over 4-bit digits
+const WORKGROUP_SIZE: u32 = 256u;
+const RADIX: u32 = 16u;
+
+struct Params {
+    count: u32,
+    // Bit offset of the digit sorted by this pass
+    shift: u32,
+    src_offset: u32,
+    dst_offset: u32,
+    block_count: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read> src_keys: array<u32>;
+
+@group(0) @binding(1)
+var<storage, read_write> dst_keys: array<u32>;
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+// Digit counts of each block, digit-major so that their exclusive scan gives
+// the position of every (digit, block) in the output
+@group(0) @binding(3)
+var<storage, read_write> histograms: array<u32>;
+
+@group(0) @binding(4)
+var<storage, read> offsets: array<u32>;
+
+@group(0) @binding(5)
+var<storage, read> src_values: array<u32>;
+
+@group(0) @binding(6)
+var<storage, read_write> dst_values: array<u32>;
+
+// Inclusive counts of each digit in the block, as 16-bit counters packed in pairs:
+// digits 0-7 in low_counts and digits 8-15 in high_counts
+var<workgroup> low_counts: array<vec4<u32>, WORKGROUP_SIZE>;
+var<workgroup> high_counts: array<vec4<u32>, WORKGROUP_SIZE>;
+
+fn workgroup_index(workgroup_id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return workgroup_id.x + (workgroup_id.y + workgroup_id.z * num_workgroups.y) * num_workgroups.x;
+}
+
+fn key_digit(key: u32) -> u32 {
+    return (key >> params.shift) & (RADIX - 1u);
+}
+
+fn counter(low: vec4<u32>, high: vec4<u32>, digit: u32) -> u32 {
+    var pair_counts: vec4<u32> = low;
+    if digit >= 8u {
+        pair_counts = high;
+    }
+    return (pair_counts[(digit >> 1u) & 3u] >> ((digit & 1u) * 16u)) & 0xffffu;
+}
+
+// Must be called by every invocation of the workgroup
+fn count_digits(local_index: u32, digit: u32, valid: bool) {
+    var low: vec4<u32> = vec4<u32>(0u);
+    var high: vec4<u32> = vec4<u32>(0u);
+    if valid {
+        let bit: u32 = 1u << ((digit & 1u) * 16u);
+        if digit < 8u {
+            low[(digit >> 1u) & 3u] = bit;
+        } else {
+            high[(digit >> 1u) & 3u] = bit;
+        }
+    }
+    low_counts[local_index] = low;
+    high_counts[local_index] = high;
+    workgroupBarrier();
+
+    // Hillis-Steele inclusive scan
+    for (var offset: u32 = 1u; offset < WORKGROUP_SIZE; offset *= 2u) {
+        if local_index >= offset {
+            low += low_counts[local_index - offset];
+            high += high_counts[local_index - offset];
+        }
+        workgroupBarrier();
+        low_counts[local_index] = low;
+        high_counts[local_index] = high;
+        workgroupBarrier();
+    }
+}
+
+// Output position of the key, counting the keys with the same digit before it (stable)
+fn sorted_position(local_index: u32, block: u32, digit: u32, valid: bool) -> u32 {
+    count_digits(local_index, digit, valid);
+    let rank: u32 = counter(low_counts[local_index], high_counts[local_index], digit) - 1u;
+    return params.dst_offset + offsets[digit * params.block_count + block] + rank;
+}
+
+@compute @workgroup_size(256)
+fn histogram(
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = workgroup_index(workgroup_id, num_workgroups);
+    // Surplus workgroup of a dispatch split over several dimensions, the same for the whole
+    // workgroup so returning before the barriers is safe
+    if block >= params.block_count {
+        return;
+    }
+    let index: u32 = block * WORKGROUP_SIZE + local_index;
+    let valid: bool = index < params.count;
+    var digit: u32 = 0u;
+    if valid {
+        digit = key_digit(src_keys[params.src_offset + index]);
+    }
+    count_digits(local_index, digit, valid);
+
+    if local_index < RADIX {
+        let last: u32 = WORKGROUP_SIZE - 1u;
+        histograms[local_index * params.block_count + block] =
+            counter(low_counts[last], high_counts[last], local_index);
+    }
+}
+
+@compute @workgroup_size(256)
+fn scatter_keys(
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = workgroup_index(workgroup_id, num_workgroups);
+    // Surplus workgroup, as in histogram
+    if block >= params.block_count {
+        return;
+    }
+    let index: u32 = block * WORKGROUP_SIZE + local_index;
+    let valid: bool = index < params.count;
+    var key: u32 = 0u;
+    if valid {
+        key = src_keys[params.src_offset + index];
+    }
+    let position: u32 = sorted_position(local_index, block, key_digit(key), valid);
+    if valid {
+        dst_keys[position] = key;
+    }
+}
+
+@compute @workgroup_size(256)
+fn scatter_pairs(
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = workgroup_index(workgroup_id, num_workgroups);
+    // Surplus workgroup, as in histogram
+    if block >= params.block_count {
+        return;
+    }
+    let index: u32 = block * WORKGROUP_SIZE + local_index;
+    let valid: bool = index < params.count;
+    var key: u32 = 0u;
+    if valid {
+        key = src_keys[params.src_offset + index];
+    }
+    let position: u32 = sorted_position(local_index, block, key_digit(key), valid);
+    if valid {
+        dst_keys[position] = key;
+        dst_values[position] = src_values[params.src_offset + index];
+    }
+}
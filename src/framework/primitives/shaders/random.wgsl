@@ -0,0 +1,74 @@
+// Fills buffers with random values, appended to philox.wgsl
+// -> Element i is lane i % 4 of philox4x32((i / 4, stream low, stream high, 0), seed), so
+//    values only depend on the seed, the stream and their index
+// -> Floats are written as their bits, so every entry point shares one u32 output binding
+struct Params {
+    count: u32,
+    seed_low: u32,
+    seed_high: u32,
+    stream_low: u32,
+    stream_high: u32,
+    mean: f32,
+    std_dev: f32,
+}
+
+@group(0) @binding(0)
+var<storage, read_write> output: array<u32>;
+
+@group(0) @binding(1)
+var<uniform> params: Params;
+
+// Random bits of the block of 4 elements starting at 4 * block
+fn block_bits(block: u32) -> vec4<u32> {
+    let counter: vec4<u32> = vec4<u32>(block, params.stream_low, params.stream_high, 0u);
+    return philox4x32(counter, vec2<u32>(params.seed_low, params.seed_high));
+}
+
+fn block_index(id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return id.x + (id.y + id.z * num_workgroups.y) * num_workgroups.x * 256u;
+}
+
+fn write_block(block: u32, values: vec4<u32>) {
+    for (var lane: u32 = 0u; lane < 4u; lane++) {
+        let index: u32 = block * 4u + lane;
+        if index < params.count {
+            output[index] = values[lane];
+        }
+    }
+}
+
+@compute @workgroup_size(256)
+fn fill_u32(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = block_index(id, num_workgroups);
+    write_block(block, block_bits(block));
+}
+
+@compute @workgroup_size(256)
+fn fill_uniform_f32(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = block_index(id, num_workgroups);
+    let bits: vec4<u32> = block_bits(block);
+    let values: vec4<f32> = vec4<f32>(
+        random_uniform_f32(bits.x),
+        random_uniform_f32(bits.y),
+        random_uniform_f32(bits.z),
+        random_uniform_f32(bits.w),
+    );
+    write_block(block, bitcast<vec4<u32>>(values));
+}
+
+@compute @workgroup_size(256)
+fn fill_normal_f32(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = block_index(id, num_workgroups);
+    let bits: vec4<u32> = block_bits(block);
+    let normals: vec4<f32> = vec4<f32>(random_normal_f32x2(bits.xy), random_normal_f32x2(bits.zw));
+    write_block(block, bitcast<vec4<u32>>(params.mean + params.std_dev * normals));
+}
@@ -0,0 +1,63 @@
+// Specialised before compilation:
+// ELEMENT_TYPE, IDENTITY_VALUE and COMBINE_EXPRESSION (of a and b)
+alias Element = ELEMENT_TYPE;
+
+const WORKGROUP_SIZE: u32 = 256u;
+
+struct Params {
+    count: u32,
+    // Workgroups with an output, dispatches split over several dimensions have more
+    group_count: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read> input: array<Element>;
+
+@group(0) @binding(1)
+var<storage, read_write> output: array<Element>;
+
+@group(0) @binding(2)
+var<uniform> params: Params;
+
+var<workgroup> partials: array<Element, WORKGROUP_SIZE>;
+
+fn identity() -> Element {
+    return IDENTITY_VALUE;
+}
+
+fn combine(a: Element, b: Element) -> Element {
+    return COMBINE_EXPRESSION;
+}
+
+// Each workgroup reduces 2 * WORKGROUP_SIZE elements into output[workgroup index]
+@compute @workgroup_size(256)
+fn reduce(
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let group_index: u32 = workgroup_id.x + (workgroup_id.y + workgroup_id.z * num_workgroups.y) * num_workgroups.x;
+    let first: u32 = group_index * 2u * WORKGROUP_SIZE + local_index;
+    let second: u32 = first + WORKGROUP_SIZE;
+
+    var value: Element = identity();
+    if first < params.count {
+        value = input[first];
+    }
+    if second < params.count {
+        value = combine(value, input[second]);
+    }
+    partials[local_index] = value;
+    workgroupBarrier();
+
+    for (var stride: u32 = WORKGROUP_SIZE / 2u; stride > 0u; stride /= 2u) {
+        if local_index < stride {
+            partials[local_index] = combine(partials[local_index], partials[local_index + stride]);
+        }
+        workgroupBarrier();
+    }
+
+    if local_index == 0u && group_index < params.group_count {
+        output[group_index] = partials[0];
+    }
+}
@@ -0,0 +1,90 @@
+// Specialised before compilation: ELEMENT_TYPE and ZERO_VALUE
+alias Element = ELEMENT_TYPE;
+
+const WORKGROUP_SIZE: u32 = 256u;
+
+struct Params {
+    count: u32,
+    // Non-zero for an inclusive scan
+    inclusive: u32,
+    // Blocks with a sum, dispatches split over several dimensions have more workgroups
+    block_count: u32,
+}
+
+@group(0) @binding(0)
+var<storage, read> input: array<Element>;
+
+@group(0) @binding(1)
+var<storage, read_write> output: array<Element>;
+
+// Total of each block, written by scan_blocks
+@group(0) @binding(2)
+var<storage, read_write> block_sums: array<Element>;
+
+@group(0) @binding(3)
+var<uniform> params: Params;
+
+// Exclusive scan of block_sums, read by add_block_offsets
+@group(0) @binding(4)
+var<storage, read> block_offsets: array<Element>;
+
+var<workgroup> sums: array<Element, WORKGROUP_SIZE>;
+
+fn workgroup_index(workgroup_id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return workgroup_id.x + (workgroup_id.y + workgroup_id.z * num_workgroups.y) * num_workgroups.x;
+}
+
+// Scans blocks of WORKGROUP_SIZE elements independently
+@compute @workgroup_size(256)
+fn scan_blocks(
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = workgroup_index(workgroup_id, num_workgroups);
+    let index: u32 = block * WORKGROUP_SIZE + local_index;
+
+    var value: Element = ZERO_VALUE;
+    if index < params.count {
+        value = input[index];
+    }
+    sums[local_index] = value;
+    workgroupBarrier();
+
+    // Hillis-Steele inclusive scan
+    for (var offset: u32 = 1u; offset < WORKGROUP_SIZE; offset *= 2u) {
+        var sum: Element = sums[local_index];
+        if local_index >= offset {
+            sum += sums[local_index - offset];
+        }
+        workgroupBarrier();
+        sums[local_index] = sum;
+        workgroupBarrier();
+    }
+
+    if index < params.count {
+        if params.inclusive != 0u {
+            output[index] = sums[local_index];
+        } else if local_index == 0u {
+            output[index] = ZERO_VALUE;
+        } else {
+            output[index] = sums[local_index - 1u];
+        }
+    }
+    if local_index == WORKGROUP_SIZE - 1u && block < params.block_count {
+        block_sums[block] = sums[local_index];
+    }
+}
+
+@compute @workgroup_size(256)
+fn add_block_offsets(
+    @builtin(local_invocation_index) local_index: u32,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+) {
+    let block: u32 = workgroup_index(workgroup_id, num_workgroups);
+    let index: u32 = block * WORKGROUP_SIZE + local_index;
+    if index < params.count {
+        output[index] += block_offsets[block];
+    }
+}
@@ -0,0 +1,395 @@
+use std::{mem::size_of, ops::Range};
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{BindGroup, Buffer, CommandEncoder, CommandEncoderDescriptor, Device, Queue};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{compute_kernel::ComputeKernel, gpu_buffer::GpuBuffer},
+};
+
+use super::{
+    begin_compute_pass, create_params_buffer, create_scratch_buffer, scan::Scan, WORKGROUP_SIZE,
+};
+
+// Bits sorted per pass, must match the shader
+const RADIX_BITS: u32 = 4;
+const RADIX: u32 = 1 << RADIX_BITS;
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+struct Params {
+    count: u32,
+    shift: u32,
+    src_offset: u32,
+    dst_offset: u32,
+    block_count: u32,
+    _padding: [u32; 3],
+}
+
+// Stable least significant digit radix sort of u32 keys, optionally carrying u32 values
+// -> Each pass counts the 4-bit digits of every block of 256 keys, scans the counts into
+//    output positions and scatters the keys, alternating with a scratch buffer
+// -> Fits within the default Limits, which cap a single sort at 32M keys (128MB bindings)
+pub struct RadixSort {
+    key_bits: u32,
+    histogram_kernel: ComputeKernel,
+    scatter_keys_kernel: ComputeKernel,
+    scatter_pairs_kernel: ComputeKernel,
+    scan: Scan<u32>,
+}
+
+impl RadixSort {
+    #[track_caller]
+    pub fn new(device: &Device) -> Result<Self, FrameworkError> {
+        let source: &str = include_str!("shaders/radix_sort.wgsl");
+        Ok(Self {
+            key_bits: u32::BITS,
+            histogram_kernel: ComputeKernel::builder(source, "histogram")
+                .with_label("radix_sort_histogram")
+                .build(device)?,
+            scatter_keys_kernel: ComputeKernel::builder(source, "scatter_keys")
+                .with_label("radix_sort_scatter_keys")
+                .build(device)?,
+            scatter_pairs_kernel: ComputeKernel::builder(source, "scatter_pairs")
+                .with_label("radix_sort_scatter_pairs")
+                .build(device)?,
+            scan: Scan::new(device)?,
+        })
+    }
+
+    // Only sorts by the lowest `key_bits` bits, skipping passes when keys are known to be small
+    // (e.g. cell indices of a spatial hash)
+    pub fn with_key_bits(mut self, key_bits: u32) -> Self {
+        self.key_bits = key_bits.clamp(1, u32::BITS);
+        self
+    }
+
+    pub fn key_bits(&self) -> u32 {
+        self.key_bits
+    }
+
+    // <---- Sorting ---->
+    // Blocks until sorted
+    #[track_caller]
+    pub fn sort_keys(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        keys: &GpuBuffer<u32>,
+    ) -> Result<(), FrameworkError> {
+        self.sort_keys_range(device, queue, keys, 0..keys.len())
+    }
+
+    #[track_caller]
+    pub fn sort_keys_range(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        keys: &GpuBuffer<u32>,
+        range: Range<usize>,
+    ) -> Result<(), FrameworkError> {
+        check_range(keys, &range)?;
+        self.submit(device, queue, |encoder| {
+            self.record_keys(device, encoder, keys.buffer(), to_u32_range(&range))
+        })
+    }
+
+    // Values are reordered along with their keys
+    #[track_caller]
+    pub fn sort_pairs(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        keys: &GpuBuffer<u32>,
+        values: &GpuBuffer<u32>,
+    ) -> Result<(), FrameworkError> {
+        self.sort_pairs_range(device, queue, keys, values, 0..keys.len())
+    }
+
+    #[track_caller]
+    pub fn sort_pairs_range(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        keys: &GpuBuffer<u32>,
+        values: &GpuBuffer<u32>,
+        range: Range<usize>,
+    ) -> Result<(), FrameworkError> {
+        check_range(keys, &range)?;
+        check_range(values, &range)?;
+        self.submit(device, queue, |encoder| {
+            self.record_pairs(
+                device,
+                encoder,
+                keys.buffer(),
+                values.buffer(),
+                to_u32_range(&range),
+            )
+        })
+    }
+
+    fn submit(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        record: impl FnOnce(&mut CommandEncoder) -> Result<(), FrameworkError>,
+    ) -> Result<(), FrameworkError> {
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("radix_sort_encoder"),
+            });
+        record(&mut encoder)?;
+        queue.submit(Some(encoder.finish()));
+        Ok(())
+    }
+
+    // <---- Recording ---->
+    // Sorts the elements of `range` in place, the buffer must have the STORAGE usage
+    #[track_caller]
+    pub fn record_keys(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        keys: &Buffer,
+        range: Range<u32>,
+    ) -> Result<(), FrameworkError> {
+        self.record(device, encoder, keys, None, range)
+    }
+
+    #[track_caller]
+    pub fn record_pairs(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        keys: &Buffer,
+        values: &Buffer,
+        range: Range<u32>,
+    ) -> Result<(), FrameworkError> {
+        self.record(device, encoder, keys, Some(values), range)
+    }
+
+    #[track_caller]
+    fn record(
+        &self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        keys: &Buffer,
+        values: Option<&Buffer>,
+        range: Range<u32>,
+    ) -> Result<(), FrameworkError> {
+        let count: u32 = range.end.saturating_sub(range.start);
+        if count < 2 {
+            return Ok(());
+        }
+        let block_count: u32 = count.div_ceil(WORKGROUP_SIZE);
+        let scratch_keys: Buffer = create_scratch_buffer::<u32>(device, count, "radix_sort_keys")?;
+        let scratch_values: Option<Buffer> = values
+            .map(|_| create_scratch_buffer::<u32>(device, count, "radix_sort_values"))
+            .transpose()?;
+        let histograms: Buffer =
+            create_scratch_buffer::<u32>(device, RADIX * block_count, "radix_sort_histograms")?;
+        let offsets: Buffer =
+            create_scratch_buffer::<u32>(device, RADIX * block_count, "radix_sort_offsets")?;
+
+        let passes: u32 = self.key_bits.div_ceil(RADIX_BITS);
+        for pass in 0..passes {
+            // Even passes move the range into the scratch buffers, odd passes move it back
+            let to_scratch: bool = pass % 2 == 0;
+            let (src_keys, dst_keys): (&Buffer, &Buffer) = match to_scratch {
+                true => (keys, &scratch_keys),
+                false => (&scratch_keys, keys),
+            };
+            let params: Buffer = create_params_buffer(
+                device,
+                &Params {
+                    count,
+                    shift: pass * RADIX_BITS,
+                    src_offset: if to_scratch { range.start } else { 0 },
+                    dst_offset: if to_scratch { 0 } else { range.start },
+                    block_count,
+                    _padding: [0; 3],
+                },
+                "radix_sort_params",
+            )?;
+
+            // Count digits
+            let bind_group: BindGroup = self
+                .histogram_kernel
+                .create_bind_group(device, &[(0, src_keys), (2, &params), (3, &histograms)])?;
+            {
+                let mut compute_pass = begin_compute_pass(encoder, "radix_sort_histogram");
+                self.histogram_kernel.dispatch_with(
+                    &mut compute_pass,
+                    &bind_group,
+                    self.histogram_kernel.workgroups_for(count),
+                );
+            }
+            self.scan.record(
+                device,
+                encoder,
+                &histograms,
+                &offsets,
+                RADIX * block_count,
+                false,
+            )?;
+
+            // Scatter
+            let (kernel, bind_group): (&ComputeKernel, BindGroup) =
+                match (values, scratch_values.as_ref()) {
+                    (Some(values), Some(scratch_values)) => {
+                        let (src_values, dst_values): (&Buffer, &Buffer) = match to_scratch {
+                            true => (values, scratch_values),
+                            false => (scratch_values, values),
+                        };
+                        (
+                            &self.scatter_pairs_kernel,
+                            self.scatter_pairs_kernel.create_bind_group(
+                                device,
+                                &[
+                                    (0, src_keys),
+                                    (1, dst_keys),
+                                    (2, &params),
+                                    (4, &offsets),
+                                    (5, src_values),
+                                    (6, dst_values),
+                                ],
+                            )?,
+                        )
+                    }
+                    _ => (
+                        &self.scatter_keys_kernel,
+                        self.scatter_keys_kernel.create_bind_group(
+                            device,
+                            &[(0, src_keys), (1, dst_keys), (2, &params), (4, &offsets)],
+                        )?,
+                    ),
+                };
+            let mut compute_pass = begin_compute_pass(encoder, "radix_sort_scatter");
+            kernel.dispatch_with(&mut compute_pass, &bind_group, kernel.workgroups_for(count));
+        }
+
+        // An odd number of passes leaves the result in the scratch buffers
+        if passes % 2 == 1 {
+            let offset: u64 = (range.start as usize * size_of::<u32>()) as u64;
+            let size: u64 = (count as usize * size_of::<u32>()) as u64;
+            encoder.copy_buffer_to_buffer(&scratch_keys, 0, keys, offset, size);
+            if let (Some(values), Some(scratch_values)) = (values, scratch_values.as_ref()) {
+                encoder.copy_buffer_to_buffer(scratch_values, 0, values, offset, size);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_range(buffer: &GpuBuffer<u32>, range: &Range<usize>) -> Result<(), FrameworkError> {
+    if range.start > range.end || range.end > buffer.len() {
+        return Err(FrameworkError::InvalidBufferRange {
+            label: buffer.label().unwrap_or("<unlabelled>").to_owned(),
+            message: format!("sort range {range:?} exceeds length {}", buffer.len()),
+        });
+    }
+    Ok(())
+}
+
+fn to_u32_range(range: &Range<usize>) -> Range<u32> {
+    range.start as u32..range.end as u32
+}
+
+// Reference implementations, stable like the GPU sort
+pub fn cpu_sort_keys(keys: &mut [u32]) {
+    keys.sort();
+}
+
+pub fn cpu_sort_pairs(keys: &mut [u32], values: &mut [u32]) {
+    let mut pairs: Vec<(u32, u32)> = keys.iter().copied().zip(values.iter().copied()).collect();
+    pairs.sort_by_key(|&(key, _value)| key);
+    for (index, (key, value)) in pairs.into_iter().enumerate() {
+        keys[index] = key;
+        values[index] = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::{gpu_buffer::GpuBuffer, gpu_context::GPUContext},
+        primitives::split_dispatch_context,
+        verification::kernel_check::uniform_u32,
+    };
+
+    use super::{cpu_sort_pairs, RadixSort};
+
+    // One block, just over one, a non-multiple of 256, and 5 blocks dispatched as 6
+    const COUNTS: [usize; 6] = [0, 1, 255, 257, 1000, 1100];
+
+    #[test]
+    fn sort_keys_matches_slice_sort() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        let sort: RadixSort = RadixSort::new(&context.device)?;
+        for count in COUNTS {
+            let mut expected: Vec<u32> = uniform_u32(count as u64, count);
+            let keys: GpuBuffer<u32> =
+                GpuBuffer::storage(&context.device, &expected, Some("keys"))?;
+            sort.sort_keys(&context.device, &context.queue, &keys)?;
+            expected.sort();
+            assert_eq!(
+                keys.read(&context.device, &context.queue)?,
+                expected,
+                "{count}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sort_pairs_is_stable() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        let sort: RadixSort = RadixSort::new(&context.device)?;
+        for count in COUNTS {
+            // Few distinct keys, values record the original order
+            let mut expected_keys: Vec<u32> = uniform_u32(count as u64, count)
+                .into_iter()
+                .map(|key| key % 7 * 0x1234_5678)
+                .collect();
+            let mut expected_values: Vec<u32> = (0..count as u32).collect();
+            let keys: GpuBuffer<u32> =
+                GpuBuffer::storage(&context.device, &expected_keys, Some("keys"))?;
+            let values: GpuBuffer<u32> =
+                GpuBuffer::storage(&context.device, &expected_values, Some("values"))?;
+            sort.sort_pairs(&context.device, &context.queue, &keys, &values)?;
+            cpu_sort_pairs(&mut expected_keys, &mut expected_values);
+            assert_eq!(
+                keys.read(&context.device, &context.queue)?,
+                expected_keys,
+                "{count}"
+            );
+            assert_eq!(
+                values.read(&context.device, &context.queue)?,
+                expected_values,
+                "{count}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn sort_range_with_key_bits() -> Result<(), FrameworkError> {
+        let context: GPUContext = split_dispatch_context();
+        // An odd number of passes, ending in the scratch buffers
+        let sort: RadixSort = RadixSort::new(&context.device)?.with_key_bits(12);
+        let mut expected: Vec<u32> = uniform_u32(7, 1500)
+            .into_iter()
+            .map(|key| key & 0xfff)
+            .collect();
+        let keys: GpuBuffer<u32> = GpuBuffer::storage(&context.device, &expected, Some("keys"))?;
+        sort.sort_keys_range(&context.device, &context.queue, &keys, 100..1400)?;
+        expected[100..1400].sort();
+        assert_eq!(keys.read(&context.device, &context.queue)?, expected);
+        assert!(sort
+            .sort_keys_range(&context.device, &context.queue, &keys, 100..1501)
+            .is_err());
+        Ok(())
+    }
+}
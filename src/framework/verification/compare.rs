@@ -0,0 +1,385 @@
+use std::fmt::{self, Debug};
+
+use bytemuck::Pod;
+
+use crate::framework::{error::FrameworkError, fft::complex::Complex};
+
+// Mismatches listed in a comparison, the others are only counted
+pub const DEFAULT_MAX_REPORTED: usize = 8;
+
+// Distance between an output element and its expected value
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Difference {
+    pub absolute: f64,
+    // Relative to the expected value, infinite when only the expected value is zero
+    pub relative: f64,
+    // Representable values in between, the absolute difference for integers
+    pub ulps: u64,
+}
+
+impl Difference {
+    pub const ZERO: Self = Self {
+        absolute: 0.0,
+        relative: 0.0,
+        ulps: 0,
+    };
+    pub const INFINITE: Self = Self {
+        absolute: f64::INFINITY,
+        relative: f64::INFINITY,
+        ulps: u64::MAX,
+    };
+
+    fn new(absolute: f64, expected_magnitude: f64, ulps: u64) -> Self {
+        let relative: f64 = match absolute == 0.0 {
+            true => 0.0,
+            false => absolute / expected_magnitude,
+        };
+        Self {
+            absolute,
+            relative,
+            ulps,
+        }
+    }
+
+    // Largest of each distance, for elements made of several components
+    fn max(self, other: Self) -> Self {
+        Self {
+            absolute: self.absolute.max(other.absolute),
+            relative: self.relative.max(other.relative),
+            ulps: self.ulps.max(other.ulps),
+        }
+    }
+}
+
+// Element types a kernel output can be compared on
+pub trait Verifiable: Pod + Debug {
+    fn difference(self, expected: Self) -> Difference;
+}
+
+impl Verifiable for u32 {
+    fn difference(self, expected: Self) -> Difference {
+        let ulps: u64 = self.abs_diff(expected) as u64;
+        Difference::new(ulps as f64, expected as f64, ulps)
+    }
+}
+
+impl Verifiable for i32 {
+    fn difference(self, expected: Self) -> Difference {
+        let ulps: u64 = self.abs_diff(expected) as u64;
+        Difference::new(ulps as f64, expected.unsigned_abs() as f64, ulps)
+    }
+}
+
+// NaNs only match NaNs, infinities only match themselves, +0 and -0 match each other
+impl Verifiable for f32 {
+    fn difference(self, expected: Self) -> Difference {
+        if self.is_nan() || expected.is_nan() {
+            return match self.is_nan() && expected.is_nan() {
+                true => Difference::ZERO,
+                false => Difference::INFINITE,
+            };
+        }
+        if self == expected {
+            return Difference::ZERO;
+        }
+        // The largest finite value is a single ULP away from infinity
+        if self.is_infinite() || expected.is_infinite() {
+            return Difference::INFINITE;
+        }
+        let ulps: u64 = ordered_bits(self).abs_diff(ordered_bits(expected));
+        Difference::new(
+            (self as f64 - expected as f64).abs(),
+            (expected as f64).abs(),
+            ulps,
+        )
+    }
+}
+
+// Components are compared separately, so a small component can't hide behind a large one
+impl Verifiable for Complex {
+    fn difference(self, expected: Self) -> Difference {
+        self.re
+            .difference(expected.re)
+            .max(self.im.difference(expected.im))
+    }
+}
+
+impl<T: Verifiable, const N: usize> Verifiable for [T; N]
+where
+    [T; N]: Pod,
+{
+    fn difference(self, expected: Self) -> Difference {
+        self.into_iter()
+            .zip(expected)
+            .map(|(actual, expected)| actual.difference(expected))
+            .fold(Difference::ZERO, Difference::max)
+    }
+}
+
+// Maps floats to integers in the same order, consecutive floats to consecutive integers
+fn ordered_bits(value: f32) -> i64 {
+    let bits: u32 = value.to_bits();
+    match bits >> 31 {
+        0 => bits as i64,
+        _ => -((bits & 0x7FFF_FFFF) as i64),
+    }
+}
+
+// An element matches when it is within any of the tolerances
+// -> The default tolerance only accepts exact matches
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tolerance {
+    pub absolute: f64,
+    pub relative: f64,
+    pub ulps: u64,
+}
+
+impl Tolerance {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_absolute(mut self, absolute: f64) -> Self {
+        self.absolute = absolute;
+        self
+    }
+
+    pub fn with_relative(mut self, relative: f64) -> Self {
+        self.relative = relative;
+        self
+    }
+
+    pub fn with_ulps(mut self, ulps: u64) -> Self {
+        self.ulps = ulps;
+        self
+    }
+
+    pub fn accepts(&self, difference: &Difference) -> bool {
+        difference.absolute <= self.absolute
+            || difference.relative <= self.relative
+            || difference.ulps <= self.ulps
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Mismatch<T> {
+    pub index: usize,
+    pub actual: T,
+    pub expected: T,
+    pub difference: Difference,
+}
+
+// Result of comparing GPU outputs to a CPU reference
+// -> Largest differences are taken over all elements, including those within tolerance
+#[derive(Clone, Debug)]
+pub struct Comparison<T> {
+    pub tolerance: Tolerance,
+    pub actual_len: usize,
+    pub expected_len: usize,
+    pub mismatch_count: usize,
+    // Lowest indices first
+    pub mismatches: Vec<Mismatch<T>>,
+    pub max_difference: Difference,
+    // Index of the largest absolute difference, None when every element matches exactly
+    pub worst_index: Option<usize>,
+}
+
+impl<T: Verifiable> Comparison<T> {
+    // Elements past the shorter of the two slices are not compared
+    pub fn new(actual: &[T], expected: &[T], tolerance: Tolerance, max_reported: usize) -> Self {
+        let mut comparison: Self = Self {
+            tolerance,
+            actual_len: actual.len(),
+            expected_len: expected.len(),
+            mismatch_count: 0,
+            mismatches: Vec::new(),
+            max_difference: Difference::ZERO,
+            worst_index: None,
+        };
+        for (index, (&actual, &expected)) in actual.iter().zip(expected).enumerate() {
+            let difference: Difference = actual.difference(expected);
+            if difference.absolute > comparison.max_difference.absolute {
+                comparison.worst_index = Some(index);
+            }
+            comparison.max_difference = comparison.max_difference.max(difference);
+            if tolerance.accepts(&difference) {
+                continue;
+            }
+            comparison.mismatch_count += 1;
+            if comparison.mismatches.len() < max_reported {
+                comparison.mismatches.push(Mismatch {
+                    index,
+                    actual,
+                    expected,
+                    difference,
+                });
+            }
+        }
+        comparison
+    }
+
+    pub fn passed(&self) -> bool {
+        self.mismatch_count == 0 && self.actual_len == self.expected_len
+    }
+
+    // The summary as a VerificationFailed error when the comparison failed
+    pub fn into_result(self) -> Result<(), FrameworkError> {
+        match self.passed() {
+            true => Ok(()),
+            false => Err(FrameworkError::VerificationFailed(self.to_string())),
+        }
+    }
+}
+
+// Multi-line summary: verdict, largest differences, then the first mismatches
+impl<T: Verifiable> fmt::Display for Comparison<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let compared: usize = self.actual_len.min(self.expected_len);
+        let Tolerance {
+            absolute,
+            relative,
+            ulps,
+        } = self.tolerance;
+        write!(
+            f,
+            "{} of {compared} elements outside tolerance (absolute {absolute}, relative \
+             {relative}, {ulps} ULPs)",
+            self.mismatch_count
+        )?;
+        if self.actual_len != self.expected_len {
+            write!(
+                f,
+                "\n  {} elements produced, {} expected",
+                self.actual_len, self.expected_len
+            )?;
+        }
+        let Difference {
+            absolute,
+            relative,
+            ulps,
+        } = self.max_difference;
+        write!(
+            f,
+            "\n  largest differences: absolute {absolute:.3e}, relative {relative:.3e}, {ulps} ULPs"
+        )?;
+        if let Some(index) = self.worst_index {
+            write!(f, " (largest absolute at index {index})")?;
+        }
+        for mismatch in &self.mismatches {
+            let Difference {
+                absolute,
+                relative,
+                ulps,
+            } = mismatch.difference;
+            write!(
+                f,
+                "\n  [{}] {:?}, expected {:?} (absolute {absolute:.3e}, relative {relative:.3e}, \
+                 {ulps} ULPs)",
+                mismatch.index, mismatch.actual, mismatch.expected
+            )?;
+        }
+        if self.mismatch_count > self.mismatches.len() {
+            write!(
+                f,
+                "\n  ... and {} more",
+                self.mismatch_count - self.mismatches.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn compare<T: Verifiable>(actual: &[T], expected: &[T], tolerance: Tolerance) -> Comparison<T> {
+    Comparison::new(actual, expected, tolerance, DEFAULT_MAX_REPORTED)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compare, Comparison, Difference, Tolerance, Verifiable};
+
+    #[test]
+    fn f32_special_values() {
+        assert_eq!(0.0f32.difference(-0.0), Difference::ZERO);
+        assert_eq!((-0.0f32).difference(0.0), Difference::ZERO);
+        assert_eq!(f32::NAN.difference(f32::NAN), Difference::ZERO);
+        assert_eq!(f32::NAN.difference(1.0), Difference::INFINITE);
+        assert_eq!(1.0f32.difference(f32::NAN), Difference::INFINITE);
+        assert_eq!(f32::INFINITY.difference(f32::INFINITY), Difference::ZERO);
+        assert_eq!(
+            f32::INFINITY.difference(f32::NEG_INFINITY),
+            Difference::INFINITE
+        );
+        assert_eq!(f32::MAX.difference(f32::INFINITY), Difference::INFINITE);
+    }
+
+    #[test]
+    fn f32_ulps_across_zero() {
+        let smallest: f32 = f32::from_bits(1);
+        assert_eq!(smallest.difference(0.0).ulps, 1);
+        assert_eq!(smallest.difference(-0.0).ulps, 1);
+        assert_eq!(smallest.difference(-smallest).ulps, 2);
+        assert_eq!((-smallest).difference(smallest).ulps, 2);
+        assert_eq!(
+            1.0f32.difference(f32::from_bits(1.0f32.to_bits() + 1)).ulps,
+            1
+        );
+        assert_eq!((-1.0f32).difference(1.0).ulps, 2 * 1.0f32.to_bits() as u64);
+
+        // Relative to the expected value, infinite when it is zero
+        let difference: Difference = smallest.difference(0.0);
+        assert_eq!(difference.absolute, smallest as f64);
+        assert_eq!(difference.relative, f64::INFINITY);
+    }
+
+    #[test]
+    fn comparison_reports_lowest_indices() {
+        let expected: Vec<f32> = (0..20).map(|i| i as f32).collect();
+        let mut actual: Vec<f32> = expected.clone();
+        for index in [3, 7, 11] {
+            actual[index] += 0.5;
+        }
+        actual[15] += 2.0;
+
+        let comparison: Comparison<f32> =
+            Comparison::new(&actual, &expected, Tolerance::new().with_absolute(0.25), 2);
+        assert!(!comparison.passed());
+        assert_eq!(comparison.mismatch_count, 4);
+        let indices: Vec<usize> = comparison.mismatches.iter().map(|m| m.index).collect();
+        assert_eq!(indices, [3, 7]);
+        assert_eq!(comparison.worst_index, Some(15));
+        let summary: String = comparison.to_string();
+        assert!(summary.starts_with("4 of 20 elements outside tolerance"));
+        assert!(summary.contains("\n  [3] 3.5, expected 3.0"));
+        assert!(summary.ends_with("... and 2 more"));
+
+        assert!(
+            compare(&actual, &expected, Tolerance::new().with_absolute(2.0))
+                .into_result()
+                .is_ok()
+        );
+        // Missing elements fail even when the compared ones match
+        assert!(!compare(
+            &actual[..10],
+            &expected,
+            Tolerance::new().with_absolute(2.0)
+        )
+        .passed());
+    }
+
+    #[test]
+    fn exact_matches_have_no_worst_index() {
+        let expected: Vec<f32> = vec![0.0, 1.0, -0.0, f32::NAN, f32::INFINITY];
+        let actual: Vec<f32> = vec![-0.0, 1.0, 0.0, f32::NAN, f32::INFINITY];
+        let comparison: Comparison<f32> = compare(&actual, &expected, Tolerance::new());
+        assert!(comparison.passed());
+        assert_eq!(comparison.max_difference, Difference::ZERO);
+        assert_eq!(comparison.worst_index, None);
+        assert!(!comparison.to_string().contains("largest absolute at index"));
+
+        // Differences within tolerance are still reported
+        let comparison: Comparison<u32> =
+            compare(&[4, 6, 9], &[4, 5, 7], Tolerance::new().with_absolute(2.0));
+        assert!(comparison.passed());
+        assert_eq!(comparison.worst_index, Some(2));
+    }
+}
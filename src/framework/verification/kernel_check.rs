@@ -0,0 +1,351 @@
+use std::{mem::size_of, ops::Range};
+
+use bytemuck::Pod;
+use wgpu::{
+    BindGroup, Buffer, BufferUsages, CommandEncoder, CommandEncoderDescriptor,
+    ComputePassDescriptor,
+};
+
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{compute_kernel::ComputeKernel, gpu_buffer::GpuBuffer, gpu_context::GPUContext},
+    primitives::random::{cpu_random_f32, cpu_random_u32, Distribution},
+};
+
+use super::compare::{Comparison, Tolerance, Verifiable, DEFAULT_MAX_REPORTED};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InputKind {
+    Storage,
+    Uniform,
+}
+
+struct Input {
+    binding: u32,
+    kind: InputKind,
+    // Contents as 32-bit words, so they can be viewed as any 4-byte aligned type
+    words: Vec<u32>,
+}
+
+// Inputs of a kernel check, as seen by the CPU reference
+pub struct KernelInputs {
+    inputs: Vec<Input>,
+}
+
+impl KernelInputs {
+    // Panics if nothing is bound at `binding` or its size isn't a multiple of T's
+    pub fn get<T: Pod>(&self, binding: u32) -> &[T] {
+        bytemuck::cast_slice(&self.input(binding).words)
+    }
+
+    // Panics if no uniform is bound at `binding` or it is smaller than P
+    pub fn uniform<P: Pod>(&self, binding: u32) -> P {
+        let input: &Input = self.input(binding);
+        assert!(
+            input.kind == InputKind::Uniform,
+            "Input at binding {binding} is not a uniform"
+        );
+        let bytes: &[u8] = bytemuck::cast_slice(&input.words);
+        bytemuck::pod_read_unaligned(&bytes[..size_of::<P>()])
+    }
+
+    fn input(&self, binding: u32) -> &Input {
+        self.inputs
+            .iter()
+            .find(|input| input.binding == binding)
+            .unwrap_or_else(|| panic!("No input bound at binding {binding}"))
+    }
+}
+
+// Runs a compute kernel once and compares one of its storage buffers to a CPU reference
+// computed from the same inputs, e.g.
+//     KernelCheck::new(source, "main")
+//         .with_input(0, &uniform_f32(1, n, -1.0..1.0))
+//         .with_output(1, n)
+//         .with_tolerance(Tolerance::new().with_ulps(2))
+//         .run(&context, |inputs| inputs.get::<f32>(0).iter().map(|x| x.exp()).collect())?
+//         .into_result()
+// -> The output buffer is zeroed, unless it is also an input, for kernels working in place
+// -> Without explicit workgroups, one invocation is dispatched per output element, split as
+//    described in ComputeKernel::workgroups_for
+pub struct KernelCheck<'a> {
+    source: &'a str,
+    entry_point: &'a str,
+    label: Option<&'a str>,
+    inputs: Vec<Input>,
+    output: Option<(u32, usize)>,
+    invocations: Option<u32>,
+    workgroups: Option<(u32, u32, u32)>,
+    tolerance: Tolerance,
+    max_reported: usize,
+}
+
+impl<'a> KernelCheck<'a> {
+    pub fn new(source: &'a str, entry_point: &'a str) -> Self {
+        Self {
+            source,
+            entry_point,
+            label: None,
+            inputs: Vec::new(),
+            output: None,
+            invocations: None,
+            workgroups: None,
+            tolerance: Tolerance::default(),
+            max_reported: DEFAULT_MAX_REPORTED,
+        }
+    }
+
+    // <---- Builder ---->
+    pub fn with_label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_input<T: Pod>(mut self, binding: u32, contents: &[T]) -> Self {
+        self.inputs.push(Input {
+            binding,
+            kind: InputKind::Storage,
+            words: bytemuck::pod_collect_to_vec(contents),
+        });
+        self
+    }
+
+    pub fn with_uniform<P: Pod>(mut self, binding: u32, contents: &P) -> Self {
+        self.inputs.push(Input {
+            binding,
+            kind: InputKind::Uniform,
+            words: bytemuck::pod_collect_to_vec(std::slice::from_ref(contents)),
+        });
+        self
+    }
+
+    // Storage buffer of `len` elements read back and compared after the dispatch
+    pub fn with_output(mut self, binding: u32, len: usize) -> Self {
+        self.output = Some((binding, len));
+        self
+    }
+
+    pub fn with_invocations(mut self, invocations: u32) -> Self {
+        self.invocations = Some(invocations);
+        self
+    }
+
+    pub fn with_workgroups(mut self, x: u32, y: u32, z: u32) -> Self {
+        self.workgroups = Some((x, y, z));
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: Tolerance) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_max_reported(mut self, max_reported: usize) -> Self {
+        self.max_reported = max_reported;
+        self
+    }
+
+    // <---- Running ---->
+    // Blocks until the output is read back, then runs the reference on the inputs
+    #[track_caller]
+    pub fn run<O: Verifiable>(
+        self,
+        context: &GPUContext,
+        reference: impl FnOnce(&KernelInputs) -> Vec<O>,
+    ) -> Result<Comparison<O>, FrameworkError> {
+        let (device, queue) = (&context.device, &context.queue);
+        let label: &str = self.label.unwrap_or(self.entry_point);
+        let (output_binding, output_len): (u32, usize) = self.output.ok_or_else(|| {
+            FrameworkError::DimensionMismatch(format!("Kernel check '{label}' has no output"))
+        })?;
+
+        let kernel: ComputeKernel = ComputeKernel::builder(self.source, self.entry_point)
+            .with_label(label)
+            .build(device)?;
+        let mut buffers: Vec<(u32, GpuBuffer<u32>)> = Vec::new();
+        for input in self.inputs.iter() {
+            if input.binding == output_binding {
+                continue;
+            }
+            let usage: BufferUsages = match input.kind {
+                InputKind::Storage => BufferUsages::STORAGE,
+                InputKind::Uniform => BufferUsages::UNIFORM,
+            };
+            buffers.push((
+                input.binding,
+                GpuBuffer::new(device, &input.words, usage, Some(label))?,
+            ));
+        }
+        let output: GpuBuffer<O> = match self
+            .inputs
+            .iter()
+            .find(|input| input.binding == output_binding)
+        {
+            Some(input) => {
+                let contents: Vec<O> = bytemuck::pod_collect_to_vec(&input.words);
+                if contents.len() != output_len {
+                    return Err(FrameworkError::DimensionMismatch(format!(
+                        "Kernel check '{label}' outputs {output_len} elements in place of an \
+                         input of {}",
+                        contents.len()
+                    )));
+                }
+                GpuBuffer::storage(device, &contents, Some(label))?
+            }
+            None => GpuBuffer::zeroed(device, output_len, BufferUsages::STORAGE, Some(label))?,
+        };
+
+        let mut bindings: Vec<(u32, &Buffer)> = buffers
+            .iter()
+            .map(|(binding, buffer)| (*binding, buffer.buffer()))
+            .collect();
+        bindings.push((output_binding, output.buffer()));
+        let bind_group: BindGroup = kernel.create_bind_group(device, &bindings)?;
+        let workgroups: (u32, u32, u32) = self.workgroups.unwrap_or_else(|| {
+            kernel.workgroups_for(self.invocations.unwrap_or(output_len as u32))
+        });
+        let mut encoder: CommandEncoder =
+            device.create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("kernel_check_encoder"),
+            });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some(label),
+                timestamp_writes: None,
+            });
+            kernel.dispatch_with(&mut compute_pass, &bind_group, workgroups);
+        }
+        queue.submit(Some(encoder.finish()));
+
+        let actual: Vec<O> = output.read(device, queue)?;
+        let expected: Vec<O> = reference(&KernelInputs {
+            inputs: self.inputs,
+        });
+        Ok(Comparison::new(
+            &actual,
+            &expected,
+            self.tolerance,
+            self.max_reported,
+        ))
+    }
+}
+
+// <---- Generated inputs ---->
+// Reproducible pseudo-random inputs, from the CPU reference of the Philox generator
+pub fn uniform_u32(seed: u64, count: usize) -> Vec<u32> {
+    cpu_random_u32(seed, 0, count)
+}
+
+pub fn uniform_f32(seed: u64, count: usize, range: Range<f32>) -> Vec<f32> {
+    cpu_random_f32(seed, 0, count, Distribution::UniformF32)
+        .into_iter()
+        .map(|value| range.start + value * (range.end - range.start))
+        .collect()
+}
+
+pub fn normal_f32(seed: u64, count: usize, mean: f32, std_dev: f32) -> Vec<f32> {
+    cpu_random_f32(seed, 0, count, Distribution::Normal { mean, std_dev })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framework::{
+        error::FrameworkError,
+        gpu::gpu_context::GPUContext,
+        verification::{
+            compare::{Comparison, Tolerance},
+            verification_context,
+        },
+    };
+
+    use super::{uniform_f32, KernelCheck, KernelInputs};
+
+    // Doubles its input, off by one at every index ending in 07 when `faulty` is set
+    const DOUBLE: &str = "
+        struct Params {
+            faulty: u32,
+        }
+
+        @group(0) @binding(0)
+        var<storage, read> input: array<f32>;
+
+        @group(0) @binding(1)
+        var<storage, read_write> output: array<f32>;
+
+        @group(0) @binding(2)
+        var<uniform> params: Params;
+
+        @compute @workgroup_size(64)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            let index: u32 = id.x;
+            if index >= arrayLength(&output) {
+                return;
+            }
+            var value: f32 = input[index] * 2.0;
+            if params.faulty != 0u && index % 100u == 7u {
+                value += 1.0;
+            }
+            output[index] = value;
+        }
+    ";
+    const COUNT: usize = 300;
+
+    fn double(context: &GPUContext, faulty: u32) -> Result<Comparison<f32>, FrameworkError> {
+        KernelCheck::new(DOUBLE, "main")
+            .with_label("double")
+            .with_input(0, &uniform_f32(1, COUNT, -1.0..1.0))
+            .with_uniform(2, &faulty)
+            .with_output(1, COUNT)
+            .with_tolerance(Tolerance::new().with_ulps(1))
+            .with_max_reported(2)
+            .run(context, |inputs: &KernelInputs| {
+                inputs.get::<f32>(0).iter().map(|x| x * 2.0).collect()
+            })
+    }
+
+    #[test]
+    fn passing_check() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let comparison: Comparison<f32> = double(&context, 0)?;
+        assert!(comparison.passed(), "{comparison}");
+        assert_eq!(comparison.actual_len, COUNT);
+        comparison.into_result()
+    }
+
+    #[test]
+    fn failing_check_reports_mismatches() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        let comparison: Comparison<f32> = double(&context, 1)?;
+        assert!(!comparison.passed());
+        assert_eq!(comparison.mismatch_count, 3);
+        let indices: Vec<usize> = comparison.mismatches.iter().map(|m| m.index).collect();
+        assert_eq!(indices, [7, 107]);
+
+        let Err(FrameworkError::VerificationFailed(summary)) = comparison.into_result() else {
+            panic!("A failing comparison must be a VerificationFailed error");
+        };
+        assert!(
+            summary.starts_with("3 of 300 elements outside tolerance"),
+            "{summary}"
+        );
+        assert!(summary.contains("\n  [7] "), "{summary}");
+        assert!(summary.contains("\n  [107] "), "{summary}");
+        assert!(!summary.contains("[207]"), "{summary}");
+        assert!(summary.ends_with("... and 1 more"), "{summary}");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_kernels_fail_the_check() -> Result<(), FrameworkError> {
+        let context: GPUContext = verification_context()?;
+        assert!(context.is_strict_validation());
+        let result: Result<Comparison<f32>, FrameworkError> =
+            KernelCheck::new(&DOUBLE.replace("* 2.0", "* 2u"), "main")
+                .with_input(0, &[1.0f32])
+                .with_uniform(2, &0u32)
+                .with_output(1, 1)
+                .run(&context, |_| vec![2.0]);
+        assert!(result.is_err());
+        Ok(())
+    }
+}
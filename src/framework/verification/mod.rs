@@ -0,0 +1,33 @@
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        adapter_selection::AdapterSelection, api_trace::ApiTrace,
+        device_requirements::DeviceRequirements, gpu_context::GPUContext,
+    },
+};
+
+pub mod compare;
+pub mod kernel_check;
+
+// Context kernels are verified on, a software adapter unless overridden by the environment
+// (e.g. WGPU_FORCE_FALLBACK_ADAPTER=0 to verify on the hardware adapter)
+// -> Has strict validation, so invalid kernels and bindings fail the check instead of only
+//    being logged
+pub fn verification_context() -> Result<GPUContext, FrameworkError> {
+    verification_context_with_requirements(&DeviceRequirements::default())
+}
+
+// Lower limits than the defaults exercise code paths that would otherwise need large inputs
+// (e.g. dispatches split over several dimensions)
+pub fn verification_context_with_requirements(
+    requirements: &DeviceRequirements,
+) -> Result<GPUContext, FrameworkError> {
+    let context: GPUContext = GPUContext::new_blocking(
+        &AdapterSelection::new()
+            .with_fallback_adapter(true)
+            .with_env_overrides(),
+        requirements,
+        &ApiTrace::new(),
+    )?;
+    Ok(context.with_strict_validation(true))
+}
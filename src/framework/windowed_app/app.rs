@@ -1,13 +1,21 @@
 use std::sync::Arc;
 
 use winit::{
-    event_loop::{ControlFlow, EventLoop},
+    event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
     window::{Window, WindowAttributes},
 };
 
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        adapter_selection::AdapterSelection, api_trace::ApiTrace, compute_graph::ComputeGraph,
+        device_requirements::DeviceRequirements,
+    },
+};
+
 use super::{
-    gpu::gpu_wrapper::GPUWrapper,
-    rendering::renderer::{RenderedObjectMap, Renderer},
+    gpu::{gpu_wrapper::GPUWrapper, surface_settings::SurfaceSettings},
+    rendering::renderer::{RenderedObjectMap, Renderer, SharedBufferMap},
     timers::frame_timer::FrameTimer,
 };
 
@@ -16,11 +24,21 @@ pub struct WindowedApp {
     // Rendering
     pub window: Option<Arc<Window>>,
     pub window_attributes: WindowAttributes,
+    pub adapter_selection: AdapterSelection,
+    pub device_requirements: DeviceRequirements,
+    pub api_trace: ApiTrace,
+    pub surface_settings: SurfaceSettings,
     pub gpu_wrapper: Option<GPUWrapper>,
     pub renderer: Renderer,
     pub rendered_objects: RenderedObjectMap,
+    // Simulation
+    pub compute_graph: ComputeGraph,
+    pub shared_buffers: SharedBufferMap,
+    pub compute_iterations_per_frame: u32,
     pub frametimer: FrameTimer,
     pub target_framerate: f32,
+    // Error which stopped the event loop
+    error: Option<FrameworkError>,
 }
 
 impl WindowedApp {
@@ -28,11 +46,19 @@ impl WindowedApp {
         Self {
             window: None,
             window_attributes: WindowAttributes::default().with_title(title),
+            adapter_selection: AdapterSelection::from_env(),
+            device_requirements: DeviceRequirements::new(),
+            api_trace: ApiTrace::from_env(),
+            surface_settings: SurfaceSettings::new(),
             gpu_wrapper: None,
             renderer: Renderer::new(),
             rendered_objects: Default::default(),
+            compute_graph: ComputeGraph::new(),
+            shared_buffers: Default::default(),
+            compute_iterations_per_frame: 1,
             frametimer: Default::default(),
             target_framerate: 0.0,
+            error: None,
         }
     }
 
@@ -40,19 +66,35 @@ impl WindowedApp {
         Self {
             window: None,
             window_attributes,
+            adapter_selection: AdapterSelection::from_env(),
+            device_requirements: DeviceRequirements::new(),
+            api_trace: ApiTrace::from_env(),
+            surface_settings: SurfaceSettings::new(),
             gpu_wrapper: None,
             renderer: Renderer::new(),
             rendered_objects: Default::default(),
+            compute_graph: ComputeGraph::new(),
+            shared_buffers: Default::default(),
+            compute_iterations_per_frame: 1,
             frametimer: Default::default(),
             target_framerate: 0.0,
+            error: None,
         }
     }
 
-    pub fn run(&mut self) {
-        let event_loop: EventLoop<()> = EventLoop::new().unwrap();
+    pub fn run(&mut self) -> Result<(), FrameworkError> {
+        let event_loop: EventLoop<()> = EventLoop::new()?;
         event_loop.set_control_flow(ControlFlow::Poll);
-        event_loop
-            .run_app(self)
-            .unwrap_or_else(|err| log::error!("Event loop error: {err:?}"));
+        event_loop.run_app(self)?;
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    pub fn exit_with_error(&mut self, event_loop: &ActiveEventLoop, err: FrameworkError) {
+        log::error!("{err}");
+        self.error = Some(err);
+        event_loop.exit();
     }
 }
@@ -1,82 +1,151 @@
 use std::sync::Arc;
 
 use wgpu::{
-    Adapter, Device, DeviceDescriptor, Features, Instance, Limits, PowerPreference, PresentMode,
-    Queue, RequestAdapterOptions, Surface, SurfaceConfiguration,
+    CompositeAlphaMode, Instance, PresentMode, Surface, SurfaceCapabilities, SurfaceConfiguration,
+    TextureFormat,
 };
 use winit::{dpi::PhysicalSize, window::Window};
 
-use crate::framework::windowed_app::app::WindowedApp;
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        adapter_selection::AdapterSelection, api_trace::ApiTrace,
+        device_requirements::DeviceRequirements, gpu_context::GPUContext,
+    },
+    windowed_app::app::WindowedApp,
+};
+
+use super::surface_settings::{
+    select_alpha_mode, select_present_mode, select_surface_format, SurfaceSettings,
+};
 
 pub struct GPUWrapper {
-    _instance: Instance,
+    pub context: GPUContext,
     pub surface: Surface<'static>,
-    _adapter: Adapter,
-    pub device: Device,
-    pub queue: Queue,
     pub config: SurfaceConfiguration,
 }
 
 impl GPUWrapper {
-    async fn new(window: &Arc<Window>) -> Self {
-        // Initialise instance, adapter and surface
-        let instance: Instance = Instance::default();
-        let surface: Surface = instance.create_surface(window.clone()).unwrap();
-        let adapter: Adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                power_preference: PowerPreference::HighPerformance,
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-            })
-            .await
-            .expect("Failed to find appropriate adapter");
-
-        // Create logical device and command queue
-        let (device, queue): (Device, Queue) = adapter
-            .request_device(
-                &DeviceDescriptor {
-                    label: None,
-                    required_features: Features::empty(),
-                    required_limits: Limits::default(),
-                },
-                None,
-            )
-            .await
-            .expect("Failed to create device");
+    async fn new(
+        window: &Arc<Window>,
+        selection: &AdapterSelection,
+        requirements: &DeviceRequirements,
+        trace: &ApiTrace,
+        settings: &SurfaceSettings,
+    ) -> Result<Self, FrameworkError> {
+        // Initialise instance and surface
+        let instance: Instance = selection.create_instance();
+        let surface: Surface = instance.create_surface(window.clone())?;
+
+        // Initialise headless context compatible with the surface
+        let context: GPUContext =
+            GPUContext::new_with_instance(instance, selection, requirements, trace, Some(&surface))
+                .await?;
+
+        Self::attach_surface(context, surface, window.inner_size(), settings)
+    }
 
+    pub fn attach_surface(
+        context: GPUContext,
+        surface: Surface<'static>,
+        size: PhysicalSize<u32>,
+        settings: &SurfaceSettings,
+    ) -> Result<Self, FrameworkError> {
         // Configure surface
         // -> Create config with window dimensions
-        let mut size: PhysicalSize<u32> = window.inner_size();
+        let mut size: PhysicalSize<u32> = size;
         size.width = size.width.max(1);
         size.height = size.height.max(1);
         let mut config: SurfaceConfiguration = surface
-            .get_default_config(&adapter, size.width, size.height)
-            .unwrap();
-        // -> SRGB support
-        config.view_formats.push(config.format.add_srgb_suffix());
-        // -> Preferred Presentation Mode (Mailbox = Fast VSync)
-        config.present_mode = PresentMode::Mailbox;
+            .get_default_config(&context.adapter, size.width, size.height)
+            .ok_or(FrameworkError::UnsupportedSurface)?;
+        let capabilities: SurfaceCapabilities = surface.get_capabilities(&context.adapter);
+        // -> Output format
+        let (format, view_format): (TextureFormat, TextureFormat) =
+            select_surface_format(settings, &capabilities.formats)
+                .ok_or(FrameworkError::UnsupportedSurface)?;
+        config.format = format;
+        config.view_formats = vec![view_format];
+        log::info!("Surface format: {format:?}, render format: {view_format:?}");
+        // -> Alpha compositing
+        config.alpha_mode = select_alpha_mode(settings.alpha_mode, &capabilities.alpha_modes);
+        if config.alpha_mode != settings.alpha_mode {
+            log::warn!(
+                "Alpha mode {:?} is not supported, falling back to {:?}",
+                settings.alpha_mode,
+                CompositeAlphaMode::Auto
+            );
+        }
+        // -> Presentation mode supported by the surface
+        config.present_mode =
+            Self::supported_present_mode(&context, &surface, settings.present_mode);
         // -> Configure surface
-        surface.configure(&device, &config);
+        surface.configure(&context.device, &config);
 
-        // Update App's GPU device field
-        Self {
-            _instance: instance,
+        Ok(Self {
+            context,
             surface,
-            _adapter: adapter,
-            device,
-            queue,
             config,
+        })
+    }
+
+    // Format every pipeline rendering to the surface must target
+    pub fn render_format(&self) -> TextureFormat {
+        self.config.view_formats[0]
+    }
+
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) {
+        self.config.present_mode =
+            Self::supported_present_mode(&self.context, &self.surface, present_mode);
+        self.reconfigure_surface();
+    }
+
+    fn supported_present_mode(
+        context: &GPUContext,
+        surface: &Surface,
+        requested: PresentMode,
+    ) -> PresentMode {
+        let supported: Vec<PresentMode> = surface.get_capabilities(&context.adapter).present_modes;
+        let present_mode: PresentMode = select_present_mode(requested, &supported);
+        if present_mode != requested {
+            log::warn!(
+                "Present mode {requested:?} is not supported, falling back to {present_mode:?}"
+            );
         }
+        present_mode
+    }
+
+    pub fn resize_surface(&mut self, size: PhysicalSize<u32>) {
+        // Ensure size is non-zero
+        self.config.width = size.width.max(1);
+        self.config.height = size.height.max(1);
+        self.reconfigure_surface();
+    }
+
+    pub fn reconfigure_surface(&self) {
+        self.surface.configure(&self.context.device, &self.config);
     }
 }
 
 impl WindowedApp {
-    pub fn init_gpu(&mut self) {
+    pub fn init_gpu(&mut self) -> Result<(), FrameworkError> {
         log::debug!("Initialising GPU...");
         self.gpu_wrapper = Some(pollster::block_on(GPUWrapper::new(
             self.window.as_mut().unwrap(),
-        )));
+            &self.adapter_selection,
+            &self.device_requirements,
+            &self.api_trace,
+            &self.surface_settings,
+        ))?);
         log::debug!("Initialised GPU successfuly");
+        Ok(())
+    }
+
+    // Can be called at any time, the surface is reconfigured if it already exists
+    pub fn set_present_mode(&mut self, present_mode: PresentMode) {
+        self.surface_settings.present_mode = present_mode;
+        if let Some(gpu_wrapper) = self.gpu_wrapper.as_mut() {
+            gpu_wrapper.set_present_mode(present_mode);
+        }
     }
 }
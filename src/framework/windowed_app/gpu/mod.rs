@@ -1,2 +1,2 @@
 pub mod gpu_wrapper;
-pub mod utilities;
+pub mod surface_settings;
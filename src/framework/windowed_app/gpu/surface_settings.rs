@@ -0,0 +1,270 @@
+use wgpu::{CompositeAlphaMode, PresentMode, TextureFormat};
+
+// Encoding of the colours written by the pipelines
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorEncoding {
+    // Shader output is written as is
+    Linear,
+    // Shader output is converted from linear to sRGB on write
+    Srgb,
+}
+
+// Preferred surface precision, used when supported by the surface
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DynamicRange {
+    // 8 bits per channel
+    Standard,
+    // 10 bits per colour channel (Rgb10a2Unorm)
+    TenBit,
+    // Half float per channel (Rgba16Float)
+    Hdr,
+}
+
+#[derive(Clone, Debug)]
+pub struct SurfaceSettings {
+    // Requested presentation mode, replaced by a supported fallback if needed
+    pub present_mode: PresentMode,
+    pub color_encoding: ColorEncoding,
+    pub dynamic_range: DynamicRange,
+    // Requested alpha compositing mode, replaced by Auto if unsupported
+    pub alpha_mode: CompositeAlphaMode,
+}
+
+impl Default for SurfaceSettings {
+    fn default() -> Self {
+        Self {
+            // Mailbox = Fast VSync
+            present_mode: PresentMode::Mailbox,
+            color_encoding: ColorEncoding::Srgb,
+            dynamic_range: DynamicRange::Standard,
+            alpha_mode: CompositeAlphaMode::Auto,
+        }
+    }
+}
+
+impl SurfaceSettings {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with_present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn with_color_encoding(mut self, color_encoding: ColorEncoding) -> Self {
+        self.color_encoding = color_encoding;
+        self
+    }
+
+    pub fn with_dynamic_range(mut self, dynamic_range: DynamicRange) -> Self {
+        self.dynamic_range = dynamic_range;
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: CompositeAlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+}
+
+// Fallback chains, each ending with Fifo which every surface supports
+// -> Mailbox: Mailbox -> Fifo (stay tear-free)
+// -> Immediate: Immediate -> Mailbox -> Fifo (lowest latency first)
+// -> FifoRelaxed: FifoRelaxed -> Fifo
+// -> AutoVsync and AutoNoVsync are resolved by wgpu itself
+pub fn select_present_mode(requested: PresentMode, supported: &[PresentMode]) -> PresentMode {
+    let chain: &[PresentMode] = match requested {
+        PresentMode::AutoVsync | PresentMode::AutoNoVsync => return requested,
+        PresentMode::Mailbox => &[PresentMode::Mailbox],
+        PresentMode::Immediate => &[PresentMode::Immediate, PresentMode::Mailbox],
+        PresentMode::FifoRelaxed => &[PresentMode::FifoRelaxed],
+        PresentMode::Fifo => &[],
+    };
+    chain
+        .iter()
+        .find(|present_mode| supported.contains(present_mode))
+        .copied()
+        .unwrap_or(PresentMode::Fifo)
+}
+
+// Returns the surface format and the format pipelines render to
+// -> Hdr: Rgba16Float -> Rgb10a2Unorm -> 8-bit
+// -> TenBit: Rgb10a2Unorm -> 8-bit
+// -> 8-bit formats are taken in the surface's order of preference
+// -> High precision formats have no sRGB variant and are always linear
+pub fn select_surface_format(
+    settings: &SurfaceSettings,
+    supported: &[TextureFormat],
+) -> Option<(TextureFormat, TextureFormat)> {
+    let high_precision: &[TextureFormat] = match settings.dynamic_range {
+        DynamicRange::Standard => &[],
+        DynamicRange::TenBit => &[TextureFormat::Rgb10a2Unorm],
+        DynamicRange::Hdr => &[TextureFormat::Rgba16Float, TextureFormat::Rgb10a2Unorm],
+    };
+    let format: TextureFormat = high_precision
+        .iter()
+        .find(|format| supported.contains(format))
+        .or_else(|| {
+            supported.iter().find(|format| {
+                matches!(
+                    format.remove_srgb_suffix(),
+                    TextureFormat::Bgra8Unorm | TextureFormat::Rgba8Unorm
+                )
+            })
+        })
+        .or_else(|| supported.first())
+        .copied()?;
+    let view_format: TextureFormat = match settings.color_encoding {
+        ColorEncoding::Linear => format.remove_srgb_suffix(),
+        ColorEncoding::Srgb => format.add_srgb_suffix(),
+    };
+    Some((format, view_format))
+}
+
+pub fn select_alpha_mode(
+    requested: CompositeAlphaMode,
+    supported: &[CompositeAlphaMode],
+) -> CompositeAlphaMode {
+    match supported.contains(&requested) {
+        true => requested,
+        false => CompositeAlphaMode::Auto,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wgpu::{CompositeAlphaMode, PresentMode, TextureFormat};
+
+    use super::{
+        select_alpha_mode, select_present_mode, select_surface_format, ColorEncoding, DynamicRange,
+        SurfaceSettings,
+    };
+
+    #[test]
+    fn present_mode_fallbacks() {
+        use PresentMode::*;
+        for (requested, supported, selected) in [
+            (Mailbox, &[Fifo, Mailbox][..], Mailbox),
+            // Preferred mode missing
+            (Mailbox, &[Fifo, Immediate][..], Fifo),
+            (Immediate, &[Fifo, Immediate][..], Immediate),
+            (Immediate, &[Fifo, Mailbox][..], Mailbox),
+            (Immediate, &[Fifo][..], Fifo),
+            (FifoRelaxed, &[Fifo, FifoRelaxed][..], FifoRelaxed),
+            (FifoRelaxed, &[Fifo, Mailbox, Immediate][..], Fifo),
+            (Fifo, &[Mailbox, Immediate, Fifo][..], Fifo),
+            // Fifo even when not listed, as every surface supports it
+            (Mailbox, &[][..], Fifo),
+            // Resolved by wgpu
+            (AutoVsync, &[Fifo][..], AutoVsync),
+            (AutoNoVsync, &[Fifo][..], AutoNoVsync),
+        ] {
+            assert_eq!(
+                select_present_mode(requested, supported),
+                selected,
+                "{requested:?} among {supported:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn surface_formats() {
+        use TextureFormat::*;
+        let settings = |dynamic_range: DynamicRange, color_encoding: ColorEncoding| {
+            SurfaceSettings::new()
+                .with_dynamic_range(dynamic_range)
+                .with_color_encoding(color_encoding)
+        };
+        let (srgb, linear): (ColorEncoding, ColorEncoding) =
+            (ColorEncoding::Srgb, ColorEncoding::Linear);
+        let all: &[TextureFormat] = &[Bgra8UnormSrgb, Bgra8Unorm, Rgb10a2Unorm, Rgba16Float];
+        for (dynamic_range, encoding, supported, selected) in [
+            // High precision formats are picked even when sRGB is requested, and stay linear
+            (
+                DynamicRange::Hdr,
+                srgb,
+                all,
+                Some((Rgba16Float, Rgba16Float)),
+            ),
+            (
+                DynamicRange::Hdr,
+                linear,
+                all,
+                Some((Rgba16Float, Rgba16Float)),
+            ),
+            (
+                DynamicRange::Hdr,
+                srgb,
+                &[Bgra8UnormSrgb, Rgb10a2Unorm][..],
+                Some((Rgb10a2Unorm, Rgb10a2Unorm)),
+            ),
+            (
+                DynamicRange::TenBit,
+                srgb,
+                all,
+                Some((Rgb10a2Unorm, Rgb10a2Unorm)),
+            ),
+            // 8-bit fallback in the surface's order, with the requested encoding
+            (
+                DynamicRange::Hdr,
+                srgb,
+                &[Bgra8UnormSrgb, Bgra8Unorm][..],
+                Some((Bgra8UnormSrgb, Bgra8UnormSrgb)),
+            ),
+            (
+                DynamicRange::TenBit,
+                linear,
+                &[Rgba8UnormSrgb, Bgra8Unorm][..],
+                Some((Rgba8UnormSrgb, Rgba8Unorm)),
+            ),
+            (
+                DynamicRange::Standard,
+                srgb,
+                all,
+                Some((Bgra8UnormSrgb, Bgra8UnormSrgb)),
+            ),
+            (
+                DynamicRange::Standard,
+                linear,
+                all,
+                Some((Bgra8UnormSrgb, Bgra8Unorm)),
+            ),
+            (
+                DynamicRange::Standard,
+                srgb,
+                &[Rgba16Float, Bgra8Unorm][..],
+                Some((Bgra8Unorm, Bgra8UnormSrgb)),
+            ),
+            // First supported format when no 8-bit one is
+            (
+                DynamicRange::Standard,
+                srgb,
+                &[Rgba16Float][..],
+                Some((Rgba16Float, Rgba16Float)),
+            ),
+            (DynamicRange::Hdr, srgb, &[][..], None),
+        ] {
+            assert_eq!(
+                select_surface_format(&settings(dynamic_range, encoding), supported),
+                selected,
+                "{dynamic_range:?} {encoding:?} among {supported:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn alpha_modes() {
+        use CompositeAlphaMode::*;
+        assert_eq!(
+            select_alpha_mode(PreMultiplied, &[Opaque, PreMultiplied]),
+            PreMultiplied
+        );
+        assert_eq!(
+            select_alpha_mode(PostMultiplied, &[Opaque, PreMultiplied]),
+            Auto
+        );
+        assert_eq!(select_alpha_mode(Opaque, &[]), Auto);
+        assert_eq!(select_alpha_mode(Auto, &[Opaque]), Auto);
+    }
+}
@@ -1,6 +1,6 @@
 pub mod app;
 
 mod gpu;
-mod rendering;
+pub mod rendering;
 mod timers;
 mod window;
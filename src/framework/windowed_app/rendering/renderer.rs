@@ -1,22 +1,50 @@
-use std::borrow::Cow;
+use std::ops::Range;
 
 use rustc_hash::FxHashMap;
 use wgpu::{
-    BindGroup, BindGroupLayout, BindGroupLayoutEntry, BindingType, Buffer, CommandEncoder,
-    CommandEncoderDescriptor, Device, PipelineCompilationOptions, PipelineLayout, RenderPass,
-    RenderPipeline, ShaderModule, ShaderModuleDescriptor, ShaderSource, SurfaceTexture,
-    TextureView, TextureViewDescriptor,
+    BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingType, Buffer,
+    CommandEncoder, CommandEncoderDescriptor, Device, PipelineCompilationOptions, PipelineLayout,
+    RenderPass, RenderPipeline, ShaderModule, SurfaceTexture, TextureView, TextureViewDescriptor,
 };
 
-use crate::framework::windowed_app::{
-    app::WindowedApp,
-    gpu::{gpu_wrapper::GPUWrapper, utilities::*},
+use crate::framework::{
+    error::FrameworkError,
+    gpu::{
+        compute_graph::{ComputeGraph, GraphPass, RecordedFlips},
+        indirect::{DrawIndirectArgs, IndirectBuffer},
+        utilities::*,
+    },
+    windowed_app::{app::WindowedApp, gpu::gpu_wrapper::GPUWrapper},
 };
 
-pub type BufferMap = FxHashMap<&'static str, (u32, Buffer)>;
 pub type BindGroupMap = FxHashMap<&'static str, (u32, BindGroup)>;
 pub type BindGroupLayoutMap = FxHashMap<&'static str, BindGroupLayout>;
 pub type RenderedObjectMap = FxHashMap<&'static str, (u32, Box<dyn RenderedObject>)>;
+// Compute graph buffers bound to the render shaders, by label, at their binding
+pub type SharedBufferMap = FxHashMap<&'static str, u32>;
+
+// Draw call of the render pass
+pub enum DrawCall {
+    Direct {
+        vertices: Range<u32>,
+        instances: Range<u32>,
+    },
+    // Arguments read when the draw executes, e.g. instance counts written by a compute pass
+    Indirect {
+        args: IndirectBuffer<DrawIndirectArgs>,
+        index: usize,
+    },
+}
+
+// The quad covering the screen, drawn by the default shaders
+impl Default for DrawCall {
+    fn default() -> Self {
+        Self::Direct {
+            vertices: 0..6,
+            instances: 0..1,
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct Renderer {
@@ -28,6 +56,8 @@ pub struct Renderer {
     // Bind groups
     bind_groups: BindGroupMap,
     bind_group_layouts: BindGroupLayoutMap,
+    // Draw
+    draw_call: DrawCall,
 }
 
 impl Renderer {
@@ -35,28 +65,34 @@ impl Renderer {
         Default::default()
     }
 
-    fn init(&mut self, gpu_device: &GPUWrapper, rendered_objects: &RenderedObjectMap) {
+    pub fn draw_call(&self) -> &DrawCall {
+        &self.draw_call
+    }
+
+    pub fn set_draw_call(&mut self, draw_call: DrawCall) {
+        self.draw_call = draw_call;
+    }
+
+    fn init(
+        &mut self,
+        gpu_device: &GPUWrapper,
+        rendered_objects: &RenderedObjectMap,
+        shared_buffers: &SharedBufferMap,
+        compute_graph: &ComputeGraph,
+    ) -> Result<(), FrameworkError> {
         // Load shaders from disk
-        let vertex_shader: ShaderModule =
-            gpu_device
-                .device
-                .create_shader_module(ShaderModuleDescriptor {
-                    label: Some("vertex_shader"),
-                    source: ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                        // PATH_TO_VERTEX_SHADER
-                        "../shaders/vertex.wgsl"
-                    ))),
-                });
-        let fragment_shader: ShaderModule =
-            gpu_device
-                .device
-                .create_shader_module(ShaderModuleDescriptor {
-                    label: Some("fragment_shader"),
-                    source: ShaderSource::Wgsl(Cow::Borrowed(include_str!(
-                        // PATH_TO_FRAGMENT_SHADER
-                        "../shaders/fragment.wgsl"
-                    ))),
-                });
+        let vertex_shader: ShaderModule = create_wgsl_shader_module(
+            &gpu_device.context.device,
+            // PATH_TO_VERTEX_SHADER
+            include_str!("../shaders/vertex.wgsl"),
+            Some("vertex_shader"),
+        )?;
+        let fragment_shader: ShaderModule = create_wgsl_shader_module(
+            &gpu_device.context.device,
+            // PATH_TO_FRAGMENT_SHADER
+            include_str!("../shaders/fragment.wgsl"),
+            Some("fragment_shader"),
+        )?;
 
         // Initialise layout entry vec
         let mut layout_entries: Vec<BindGroupLayoutEntry> = Vec::new();
@@ -64,7 +100,7 @@ impl Renderer {
         // Create buffers
         for (label, (binding, object)) in rendered_objects.iter() {
             log::info!("Creating buffer: {label}");
-            let buffer: Buffer = object.to_buffer(label, &gpu_device.device);
+            let buffer: Buffer = object.to_buffer(label, &gpu_device.context.device);
             let binding_type: BindingType = object.buffer_binding_type(&buffer);
             log::info!("Adding layout entry at binding: {:}", *binding);
             layout_entries.push(create_fragment_bind_group_layout_entry(
@@ -75,27 +111,40 @@ impl Renderer {
             add_buffer(&mut self.buffers, buffer, *binding, label);
         }
 
+        // Bind shared buffers, read-only as compute passes write them
+        for (label, binding) in shared_buffers.iter() {
+            log::info!("Adding layout entry for shared buffer {label} at binding: {binding}");
+            let buffer: &Buffer = compute_graph.buffer(label)?;
+            layout_entries.push(create_render_bind_group_layout_entry(
+                *binding,
+                create_buffer_binding_type(true, true, false, buffer),
+            ));
+        }
+
         // Create bind group
         let render_bind_group_layout = create_bind_group_layout(
-            &gpu_device.device,
+            &gpu_device.context.device,
             &layout_entries,
             Some("render_bind_group_layout"),
-        );
-        let render_bind_group = create_bind_group(
-            &gpu_device.device,
+        )?;
+        let render_bind_group = create_render_bind_group(
+            &gpu_device.context.device,
             &self.buffers,
+            shared_buffers,
+            compute_graph,
+            None,
             &render_bind_group_layout,
             Some("render_bind_group"),
-        );
+        )?;
 
         // Create render pipeline
         let render_pipeline_layout = create_pipeline_layout(
-            &gpu_device.device,
+            &gpu_device.context.device,
             &[&render_bind_group_layout],
             Some("render_pipeline_layout"),
-        );
+        )?;
         let render_pipeline = create_render_pipeline(
-            &gpu_device.device,
+            &gpu_device.context.device,
             &render_pipeline_layout,
             wgpu::VertexState {
                 module: &vertex_shader,
@@ -106,11 +155,11 @@ impl Renderer {
             wgpu::FragmentState {
                 module: &fragment_shader,
                 entry_point: "main",
-                targets: &[Some(gpu_device.config.view_formats[0].into())],
+                targets: &[Some(gpu_device.render_format().into())],
                 compilation_options: PipelineCompilationOptions::default(),
             },
             Some("render_pipeline"),
-        );
+        )?;
 
         // Update renderer
         self.bind_group_layouts
@@ -118,26 +167,40 @@ impl Renderer {
         self.bind_groups.insert("render", (0, render_bind_group));
         self.pipeline = Some(render_pipeline);
         self.pipeline_layout = Some(render_pipeline_layout);
+        Ok(())
     }
 
-    pub fn render(&mut self, gpu_device: &GPUWrapper, rendered_objects: &RenderedObjectMap) {
+    // Runs the compute graph, then renders with its latest results in the same submission
+    pub fn render(
+        &mut self,
+        gpu_device: &GPUWrapper,
+        rendered_objects: &RenderedObjectMap,
+        shared_buffers: &SharedBufferMap,
+        compute_graph: &mut ComputeGraph,
+        compute_iterations: u32,
+    ) -> Result<(), FrameworkError> {
         //log::info!("Starting render");
-        let frame: SurfaceTexture = gpu_device.surface.get_current_texture().unwrap();
+        let frame: SurfaceTexture = gpu_device.surface.get_current_texture()?;
         let view: TextureView = frame.texture.create_view(&TextureViewDescriptor {
-            format: Some(gpu_device.config.view_formats[0]),
+            format: Some(gpu_device.render_format()),
             ..TextureViewDescriptor::default()
         });
 
         // Create command encoder
         let mut encoder: CommandEncoder =
             gpu_device
+                .context
                 .device
                 .create_command_encoder(&CommandEncoderDescriptor {
                     label: Some("command_encoder"),
                 });
 
         // Update buffers
-        self.update_buffers(&gpu_device.device, rendered_objects);
+        self.update_buffers(&gpu_device.context.device, rendered_objects);
+
+        // Simulate, the graph's buffers are swapped once the frame is submitted
+        let recorded: RecordedFlips =
+            compute_graph.record(&gpu_device.context.device, &mut encoder, compute_iterations)?;
 
         // Render
         {
@@ -147,27 +210,38 @@ impl Renderer {
             render_pass.set_pipeline(self.pipeline.as_ref().unwrap());
 
             // Update bind groups
-            self.bind_groups
-                .iter_mut()
-                .for_each(|(label, (index, bind_group))| {
-                    *bind_group = create_bind_group(
-                        &gpu_device.device,
-                        &self.buffers,
-                        self.bind_group_layouts.get(label).unwrap(),
-                        Some(label),
-                    );
-                    render_pass.set_bind_group(*index, bind_group, &[]);
-                });
+            for (label, (index, bind_group)) in self.bind_groups.iter_mut() {
+                *bind_group = create_render_bind_group(
+                    &gpu_device.context.device,
+                    &self.buffers,
+                    shared_buffers,
+                    compute_graph,
+                    Some(&recorded),
+                    self.bind_group_layouts.get(label).unwrap(),
+                    Some(label),
+                )?;
+                render_pass.set_bind_group(*index, bind_group, &[]);
+            }
 
             // Draw
-            render_pass.draw(0..6, 0..1);
+            match &self.draw_call {
+                DrawCall::Direct {
+                    vertices,
+                    instances,
+                } => render_pass.draw(vertices.clone(), instances.clone()),
+                DrawCall::Indirect { args, index } => {
+                    render_pass.draw_indirect(args.buffer(), args.offset(*index))
+                }
+            }
         }
 
         // Submit commands
-        gpu_device.queue.submit(Some(encoder.finish()));
+        gpu_device.context.queue.submit(Some(encoder.finish()));
+        compute_graph.apply_flips(recorded);
 
         // Present frame
         frame.present();
+        Ok(())
     }
 
     fn update_buffers(&mut self, device: &Device, rendered_objects: &RenderedObjectMap) {
@@ -179,6 +253,32 @@ impl Renderer {
     }
 }
 
+// Binds the rendered objects' buffers and the front buffers of the shared ones, as of the
+// recorded compute passes when given
+#[track_caller]
+fn create_render_bind_group(
+    device: &Device,
+    buffers: &BufferMap,
+    shared_buffers: &SharedBufferMap,
+    compute_graph: &ComputeGraph,
+    recorded: Option<&RecordedFlips>,
+    layout: &BindGroupLayout,
+    label: Option<&str>,
+) -> Result<BindGroup, FrameworkError> {
+    let mut entries: Vec<BindGroupEntry> = buffers
+        .values()
+        .map(|(binding, buffer)| create_bind_group_entry(*binding, buffer))
+        .collect();
+    for (label, binding) in shared_buffers.iter() {
+        let buffer: &Buffer = match recorded {
+            Some(recorded) => compute_graph.recorded_buffer(label, recorded)?,
+            None => compute_graph.buffer(label)?,
+        };
+        entries.push(create_bind_group_entry(*binding, buffer));
+    }
+    create_bind_group_with_entries(device, &entries, layout, label)
+}
+
 pub trait RenderedObject {
     fn to_buffer(&self, label: &str, device: &Device) -> Buffer;
     fn buffer_binding_type(&self, buffer: &Buffer) -> BindingType;
@@ -186,9 +286,13 @@ pub trait RenderedObject {
 }
 
 impl WindowedApp {
-    pub fn init_renderer(&mut self) {
-        self.renderer
-            .init(self.gpu_wrapper.as_mut().unwrap(), &self.rendered_objects);
+    pub fn init_renderer(&mut self) -> Result<(), FrameworkError> {
+        self.renderer.init(
+            self.gpu_wrapper.as_mut().unwrap(),
+            &self.rendered_objects,
+            &self.shared_buffers,
+            &self.compute_graph,
+        )
     }
 
     pub fn add_to_rendered_objects(
@@ -199,4 +303,33 @@ impl WindowedApp {
     ) {
         self.rendered_objects.insert(label, (binding, object));
     }
+
+    pub fn set_draw_call(&mut self, draw_call: DrawCall) {
+        self.renderer.set_draw_call(draw_call);
+    }
+
+    // <---- Simulation ---->
+    // Storage buffer written by compute passes and read by the render shaders at `binding`
+    // -> Needs the STORAGE usage, must be added before the renderer is initialised
+    pub fn add_shared_buffer(&mut self, label: &'static str, binding: u32, buffer: Buffer) {
+        self.compute_graph.add_buffer(label, buffer);
+        self.shared_buffers.insert(label, binding);
+    }
+
+    // The render shaders read whichever buffer of the pair was written last
+    pub fn add_shared_ping_pong(
+        &mut self,
+        label: &'static str,
+        binding: u32,
+        front: Buffer,
+        back: Buffer,
+    ) {
+        self.compute_graph.add_ping_pong(label, front, back);
+        self.shared_buffers.insert(label, binding);
+    }
+
+    // Pass run every frame before rendering, compute_iterations_per_frame times
+    pub fn add_compute_pass(&mut self, pass: GraphPass) {
+        self.compute_graph.add_pass(pass);
+    }
 }
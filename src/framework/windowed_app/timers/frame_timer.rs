@@ -4,6 +4,9 @@ pub struct FrameTimer {
     last_effective_frame: web_time::Instant,
     pub frame_time: f32,
     fps: f32,
+    // Surface events since start
+    pub skipped_frames: u32,
+    pub surface_reconfigurations: u32,
 }
 
 impl Default for FrameTimer {
@@ -20,6 +23,8 @@ impl FrameTimer {
             last_effective_frame: web_time::Instant::now(),
             frame_time: 0.0,
             fps: 0.0,
+            skipped_frames: 0,
+            surface_reconfigurations: 0,
         }
     }
 
@@ -40,6 +45,13 @@ impl FrameTimer {
                 self.frame_time * 1000.0,
                 self.fps
             );
+            if self.skipped_frames > 0 || self.surface_reconfigurations > 0 {
+                log::info!(
+                    "Skipped frames: {:}, surface reconfigurations: {:}",
+                    self.skipped_frames,
+                    self.surface_reconfigurations
+                );
+            }
         }
     }
 
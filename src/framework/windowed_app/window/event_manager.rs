@@ -3,6 +3,7 @@ use winit::event::WindowEvent;
 use crate::framework::windowed_app::app::WindowedApp;
 
 impl WindowedApp {
+    #[allow(clippy::match_single_binding)]
     pub fn handle_window_event(&mut self, event: WindowEvent) {
         match event {
             _ => (),
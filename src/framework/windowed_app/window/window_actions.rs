@@ -1,22 +1,28 @@
 use std::sync::Arc;
 
+use wgpu::SurfaceError;
 use winit::{dpi::PhysicalSize, event_loop::ActiveEventLoop, window::Window};
 
-use crate::framework::windowed_app::{app::WindowedApp, gpu::gpu_wrapper::GPUWrapper};
+use crate::framework::{error::FrameworkError, windowed_app::app::WindowedApp};
 
 impl WindowedApp {
-    pub fn create_window(&mut self, event_loop: &ActiveEventLoop) {
+    pub fn create_window(&mut self, event_loop: &ActiveEventLoop) -> Result<(), FrameworkError> {
         log::debug!("Creating window...");
-        let window: Arc<Window> = Arc::new(
-            event_loop
-                .create_window(self.window_attributes.to_owned())
-                .unwrap(),
-        );
+        let window: Arc<Window> =
+            Arc::new(event_loop.create_window(self.window_attributes.to_owned())?);
         self.window = Some(window.clone());
         log::debug!("Created window successfully");
+        Ok(())
     }
 
-    pub fn redraw_window(&mut self) {
+    pub fn redraw_window(&mut self) -> Result<(), FrameworkError> {
+        // Surface may not exist if initialisation failed, redraws can still be queued
+        // before the event loop exits
+        let (Some(gpu_device), Some(window)) = (self.gpu_wrapper.as_mut(), self.window.as_ref())
+        else {
+            return Ok(());
+        };
+
         // Update general components
         self.frametimer.update();
         self.frametimer.log();
@@ -24,32 +30,54 @@ impl WindowedApp {
         // Render frame
         if self.frametimer.is_it_time_to_refresh(self.target_framerate) {
             // Render
-            self.renderer
-                .render(self.gpu_wrapper.as_mut().unwrap(), &self.rendered_objects);
+            match self.renderer.render(
+                gpu_device,
+                &self.rendered_objects,
+                &self.shared_buffers,
+                &mut self.compute_graph,
+                self.compute_iterations_per_frame,
+            ) {
+                Ok(()) => (),
+                // -> Surface no longer matches the window, reconfigure and retry next frame
+                Err(FrameworkError::SurfaceTexture(
+                    err @ (SurfaceError::Lost | SurfaceError::Outdated),
+                )) => {
+                    log::warn!("Surface lost or outdated ({err}), reconfiguring");
+                    gpu_device.resize_surface(window.inner_size());
+                    self.frametimer.surface_reconfigurations += 1;
+                }
+                // -> Frame could not be acquired in time, skip it
+                Err(FrameworkError::SurfaceTexture(SurfaceError::Timeout)) => {
+                    log::warn!("Surface texture acquisition timed out, skipping frame");
+                    self.frametimer.skipped_frames += 1;
+                }
+                // -> Out of memory and other errors are unrecoverable
+                Err(err) => return Err(err),
+            }
         }
 
         // Request next redraw
-        self.window.as_ref().unwrap().request_redraw();
+        window.request_redraw();
+        Ok(())
     }
 
     pub fn resize_window(&mut self, new_size: PhysicalSize<u32>) {
-        // Ensure size is non-zero
-        let mut size: PhysicalSize<u32> = new_size;
-        size.width = size.width.max(1);
-        size.height = size.height.max(1);
-
-        // Update config
-        let gpu_device: &mut GPUWrapper = self.gpu_wrapper.as_mut().unwrap();
-        gpu_device.config.width = size.width;
-        gpu_device.config.height = size.height;
+        // Surface may not exist yet if initialisation failed
+        let Some(gpu_device) = self.gpu_wrapper.as_mut() else {
+            return;
+        };
 
         // Update surface
-        gpu_device
-            .surface
-            .configure(&gpu_device.device, &gpu_device.config);
-        log::debug!("Window resized to {:}x{:}", size.width, size.height);
+        gpu_device.resize_surface(new_size);
+        log::debug!(
+            "Window resized to {:}x{:}",
+            gpu_device.config.width,
+            gpu_device.config.height
+        );
 
         // Request next redraw
-        self.window.as_ref().unwrap().request_redraw();
+        if let Some(window) = self.window.as_ref() {
+            window.request_redraw();
+        }
     }
 }
@@ -7,10 +7,13 @@ use crate::framework::windowed_app::app::WindowedApp;
 
 impl ApplicationHandler for WindowedApp {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        self.create_window(event_loop);
-        self.init_gpu();
-
-        self.init_renderer();
+        if let Err(err) = self
+            .create_window(event_loop)
+            .and_then(|_| self.init_gpu())
+            .and_then(|_| self.init_renderer())
+        {
+            self.exit_with_error(event_loop, err);
+        }
     }
 
     fn window_event(
@@ -25,7 +28,11 @@ impl ApplicationHandler for WindowedApp {
                 event_loop.exit();
             }
             WindowEvent::Resized(size) => self.resize_window(size),
-            WindowEvent::RedrawRequested => self.redraw_window(),
+            WindowEvent::RedrawRequested => {
+                if let Err(err) = self.redraw_window() {
+                    self.exit_with_error(event_loop, err);
+                }
+            }
             _ => self.handle_window_event(event),
         }
     }
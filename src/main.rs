@@ -1,17 +1,65 @@
-use framework::windowed_app::app::WindowedApp;
-
-mod framework;
+use rust_gpu_framework::framework::{
+    compute_app::app::{ComputeApp, Dispatch},
+    error::FrameworkError,
+    gpu::{adapter_selection::AdapterSelection, gpu_info},
+    windowed_app::app::WindowedApp,
+};
 
 const APP_TYPE: AppType = AppType::Windowed;
 
 fn main() {
     simple_logger::init_with_level(log::Level::Info).unwrap();
+
+    // List available adapters as JSON and exit
+    if std::env::args().any(|arg| arg == "--gpu-info") {
+        print_gpu_info();
+        return;
+    }
+
     match APP_TYPE {
         AppType::Windowed => {
             let mut app: WindowedApp = WindowedApp::new("Window");
-            app.run();
+            if let Err(err) = app.run() {
+                log::error!("Application stopped: {err}");
+                std::process::exit(1);
+            }
+        }
+        AppType::Windowless => {
+            if let Err(err) = run_compute_example() {
+                log::error!("Application stopped: {err}");
+                std::process::exit(1);
+            }
         }
-        AppType::Windowless => (),
+    }
+}
+
+fn run_compute_example() -> Result<(), FrameworkError> {
+    const ELEMENT_COUNT: u32 = 1024;
+    let input: Vec<f32> = (0..ELEMENT_COUNT).map(|i| i as f32).collect();
+
+    let mut app: ComputeApp = ComputeApp::new()?;
+    app.add_storage_buffer("input", 0, &input)?;
+    app.add_storage_buffer("output", 1, &vec![0.0f32; input.len()])?;
+    app.add_kernel(
+        "square",
+        include_str!("framework/compute_app/shaders/example.wgsl"),
+        "main",
+        &["input", "output"],
+    )?;
+    app.run(&[Dispatch::new("square", (ELEMENT_COUNT.div_ceil(64), 1, 1))])?;
+
+    let output: Vec<f32> = app.read_buffer("output")?;
+    log::info!("Squared values: {:?}...", &output[..8]);
+    Ok(())
+}
+
+fn print_gpu_info() {
+    let gpus: Vec<gpu_info::GpuInfo> =
+        gpu_info::enumerate_gpus(AdapterSelection::from_env().backends);
+    gpus.iter().for_each(|gpu| gpu.log());
+    match serde_json::to_string_pretty(&gpus) {
+        Ok(json) => println!("{json}"),
+        Err(err) => log::error!("Failed to serialize GPU info: {err}"),
     }
 }
 